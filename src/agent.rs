@@ -1,64 +1,70 @@
 use std::{collections::HashMap, fmt::Display};
 
-use crate::{genes::Genotype, normalize_vector};
+use crate::{
+    behaviour::{flock, BehaviourSet, Context},
+    care::feed_young,
+    disease::{update_infection, Infection},
+    genes::{GeneSchema, Genotype, PlantGenotype},
+    memory::{update_memory, SpatialMemory},
+    neat::{Neat, NeatGenome},
+    pack::update_pack,
+    signals::{update_alarm, Alarm},
+    species::{SpeciesId, CARCASS},
+    territory::update_home,
+    vector_length, Bounds,
+};
 
 use rand::{rngs::ThreadRng, Rng};
 use uuid::Uuid;
 
-use noise::{NoiseFn, OpenSimplex};
-
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub enum AgentType {
-    Wolf(Genotype),
-    Sheep(Genotype),
-    Grass(),
+    Animal(SpeciesId, Genotype),
+    Plant(SpeciesId, PlantGenotype),
+    Carcass(),
 }
 
 impl AgentType {
-    pub fn to_int(&self) -> u8 {
+    pub fn species(&self) -> SpeciesId {
         match self {
-            AgentType::Wolf(_) => 0,
-            AgentType::Sheep(_) => 1,
-            AgentType::Grass() => 2,
+            AgentType::Animal(species, _) => *species,
+            AgentType::Plant(species, _) => *species,
+            AgentType::Carcass() => CARCASS,
         }
     }
 
-    pub fn to_name(&self) -> String {
+    pub fn to_int(&self) -> u8 {
+        self.species() as u8
+    }
+
+    pub fn genotype(&self) -> Option<&Genotype> {
         match self {
-            AgentType::Wolf(_) => "Wolf".to_string(),
-            AgentType::Sheep(_) => "Sheep".to_string(),
-            AgentType::Grass() => "Grass".to_string(),
+            AgentType::Animal(_, genes) => Some(genes),
+            _ => None,
         }
     }
 
-    pub fn genotype(&self) -> Genotype {
+    pub fn set_genotype(&mut self, new_genotype: Genotype) {
         match self {
-            AgentType::Wolf(genes) => *genes,
-            AgentType::Sheep(genes) => *genes,
-            AgentType::Grass() => panic!("Grass has no genes!"),
+            AgentType::Animal(_, genes) => *genes = new_genotype,
+            AgentType::Plant(_, _) => panic!("Plants have a plant genotype!"),
+            AgentType::Carcass() => panic!("Carcasses have no genes!"),
         }
     }
 
-    pub fn set_genotype(&mut self, new_genotype: Genotype) {
+    pub fn plant_genotype(&self) -> Option<PlantGenotype> {
         match self {
-            AgentType::Wolf(genes) => *genes = new_genotype,
-            AgentType::Sheep(genes) => *genes = new_genotype,
-            AgentType::Grass() => panic!("Grass has no genes!"),
+            AgentType::Plant(_, genes) => Some(*genes),
+            _ => None,
         }
     }
-}
 
-impl Display for AgentType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                AgentType::Wolf(_) => "Wolf",
-                AgentType::Sheep(_) => "Sheep",
-                AgentType::Grass() => "Grass",
-            }
-        )
+    pub fn is_animal(&self) -> bool {
+        matches!(self, AgentType::Animal(_, _))
+    }
+
+    pub fn is_plant(&self) -> bool {
+        matches!(self, AgentType::Plant(_, _))
     }
 }
 
@@ -69,6 +75,10 @@ pub enum State {
     Fleeing,
     Reproducing(Uuid),
     Dead,
+    Resting,
+    Defending(Uuid),
+    Homing,
+    Following,
 }
 impl State {
     pub fn to_int(&self) -> u8 {
@@ -78,6 +88,10 @@ impl State {
             State::Fleeing => 2,
             State::Reproducing(_) => 3,
             State::Dead => 4,
+            State::Resting => 5,
+            State::Defending(_) => 6,
+            State::Homing => 7,
+            State::Following => 8,
         }
     }
 }
@@ -90,19 +104,88 @@ impl Display for State {
             State::Fleeing => write!(f, "Fleeing"),
             State::Reproducing(_) => write!(f, "Reproducing"),
             State::Dead => write!(f, "Dead"),
+            State::Resting => write!(f, "Resting"),
+            State::Defending(_) => write!(f, "Defending"),
+            State::Homing => write!(f, "Homing"),
+            State::Following => write!(f, "Following"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Sex {
+    Male,
+    Female,
+}
+impl Sex {
+    pub fn from_seed(seed: f64) -> Sex {
+        if (seed as u64).is_multiple_of(2) {
+            Sex::Male
+        } else {
+            Sex::Female
+        }
+    }
+
+    pub fn to_int(self) -> u8 {
+        match self {
+            Sex::Male => 0,
+            Sex::Female => 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DeathCause {
+    Starvation,
+    Predation,
+    OldAge,
+    Poisoning,
+    Disease,
+    Fighting,
+}
+impl Display for DeathCause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeathCause::Starvation => write!(f, "Starvation"),
+            DeathCause::Predation => write!(f, "Predation"),
+            DeathCause::OldAge => write!(f, "OldAge"),
+            DeathCause::Poisoning => write!(f, "Poisoning"),
+            DeathCause::Disease => write!(f, "Disease"),
+            DeathCause::Fighting => write!(f, "Fighting"),
         }
     }
 }
 
 const MIN_HUNGER: f32 = 30.;
-const SATIETY: f32 = 40.;
-const HUNGER_RATE: f32 = 0.001;
-const BITE_SIZE: f32 = 10.;
-const WANDER_SPEED: f32 = 2.;
 const STARVING_DAMAGE: f32 = 0.1;
 pub const PLANT_GROWTH_RATE: f32 = 0.1;
+// Plants above this health drop seeds now and then
+pub const SEEDING_HEALTH: f32 = 80.;
+pub const SEEDING_CHANCE: f32 = 0.002;
+const PREGNANCY_HUNGER_MULTIPLIER: f32 = 1.5;
+const SPRINT_DRAIN: f32 = 1.;
+// Fraction of the stamina pool an exhausted agent must recover before sprinting again
+const EXHAUSTION_RECOVERY: f32 = 0.3;
+// Biomass left behind by a dead animal, per point of health_scale
+const CARCASS_BIOMASS_SCALE: f32 = 2.;
+pub const CARCASS_DECAY_RATE: f32 = 0.05;
 /* const MAX_WANDER_SPEED: f32 = 0.1; */
 
+#[derive(Clone)]
+pub struct Pregnancy {
+    pub father: Genotype,
+    // Evolving brain of the father and how long he had survived, used to rank the parents
+    pub father_brain: Option<NeatGenome>,
+    pub father_life: f32,
+    pub conceived: f32,
+    pub due: f32,
+}
+impl Pregnancy {
+    pub fn progress(&self, time: f32) -> f32 {
+        ((time - self.conceived) / (self.due - self.conceived).max(f32::EPSILON)).clamp(0., 1.)
+    }
+}
+
 #[derive(Clone)]
 // TODO:
 pub struct Agent {
@@ -114,20 +197,50 @@ pub struct Agent {
     pub id_string: String,
     pub health: f32,
     pub hunger: f32,
+    pub stamina: f32,
+    pub exhausted: bool,
     pub life: f32,
+    pub born: f32,
+    pub sex: Sex,
     pub dead: bool,
+    pub death_cause: Option<DeathCause>,
     pub state: State,
     pub seed: f64,
     pub timeout: f32,
     pub last_time: f32,
+    pub pregnancy: Option<Pregnancy>,
+    pub neat: Option<NeatGenome>,
+    // Pack id and when a packmate was last seen
+    pub pack: Option<Uuid>,
+    pub pack_seen: f32,
+    pub kills: u32,
+    // Kills a packmate made while this one was in on the hunt
+    pub assists: u32,
+    // At most one per pathogen, recovered ones are kept while the immunity lasts
+    pub infections: Vec<Infection>,
+    pub memory: SpatialMemory,
+    // Den of territorial animals and when it was last inside its territory
+    pub home: Option<(f32, f32)>,
+    pub home_seen: f32,
+    // Busy with the last kill until then
+    pub handling_until: f32,
+    // Mother of a juvenile, who feeds it while it grows up
+    pub parent: Option<Uuid>,
+    // Last alarm call and how many were made
+    pub alarm: Option<Alarm>,
+    pub calls: u32,
 }
 
 impl Agent {
-    pub fn new(kind: AgentType, position: (f32, f32), id: Uuid, seed: f64) -> Agent {
-        let genes = match kind {
-            AgentType::Wolf(genes) => Some(genes),
-            AgentType::Sheep(genes) => Some(genes),
-            AgentType::Grass() => None,
+    pub fn new(kind: AgentType, position: (f32, f32), id: Uuid, seed: f64, time: f32) -> Agent {
+        let genes = match &kind {
+            AgentType::Animal(_, genes) => Some(genes),
+            AgentType::Plant(_, _) => None,
+            AgentType::Carcass() => None,
+        };
+        let stamina = match genes {
+            Some(genes) => genes.stamina_capacity,
+            None => 0.,
         };
         let mut health_mult = 1.;
         match genes {
@@ -145,299 +258,337 @@ impl Agent {
             id_string: id.to_string(),
             health: health_mult,
             hunger: MIN_HUNGER,
+            stamina,
+            exhausted: false,
             life: 0.,
+            born: time,
+            sex: Sex::from_seed(seed * 10000.),
             dead: false,
+            death_cause: None,
             state: State::Idle,
             seed: seed * 10000.,
             timeout: 0.,
             last_time: 0.,
+            pregnancy: None,
+            neat: None,
+            pack: None,
+            pack_seen: 0.,
+            kills: 0,
+            assists: 0,
+            infections: Vec::new(),
+            memory: SpatialMemory::new(),
+            home: None,
+            home_seen: 0.,
+            handling_until: 0.,
+            parent: None,
+            alarm: None,
+            calls: 0,
         }
     }
 
     pub fn update(
         &mut self,
-        nearby_agents: Vec<(Uuid, (f32, f32))>,
-        agents: &HashMap<Uuid, Agent>,
-        genotype: Genotype,
-        noise: &OpenSimplex,
+        context: &Context,
         thread_rng: &mut ThreadRng,
-        time: f32,
+        bounds: &Bounds,
+        behaviours: &BehaviourSet,
+        neat: Option<&mut Neat>,
     ) -> HashMap<Uuid, Agent> {
         let mut modified_agents = HashMap::new();
-        self.position.0 += (self.acceleration.0)
-            .max(-genotype.movement_speed)
-            .min(genotype.movement_speed);
-        self.position.1 += (self.acceleration.1)
-            .max(-genotype.movement_speed)
-            .min(genotype.movement_speed);
-
-        self.hunger -= HUNGER_RATE * genotype.hunger_rate;
+        let genotype = &context.genotype;
+        let time = context.time;
+        // Juveniles get sturdier as they grow
+        let grown = genotype.growth(time - self.born) - genotype.growth(self.life);
+        self.health += grown * genotype.health_scale;
+        self.life = time - self.born;
+
+        // Sprint while chasing animals or running away, as long as stamina lasts
+        let chasing = match self.state {
+            State::Fleeing | State::Defending(_) => true,
+            State::Hunting(target) => context
+                .agents
+                .get(&target)
+                .is_some_and(|prey| prey.kind.is_animal()),
+            _ => false,
+        };
+        let sprinting = chasing && !self.exhausted && self.stamina > 0.;
+        let mut max_speed = genotype.movement_speed * genotype.vigor(self.life);
+        if sprinting {
+            max_speed *= genotype.sprint_multiplier;
+        }
+        let movement = (
+            (self.acceleration.0).clamp(-max_speed, max_speed),
+            (self.acceleration.1).clamp(-max_speed, max_speed),
+        );
+        // Stop short of any obstacle in the way and turn around, testing the whole move so
+        // fast agents can't skip through thin fences
+        let (moved, collided) = context.terrain.sweep(
+            (self.position.0, self.position.1),
+            (self.position.0 + movement.0, self.position.1 + movement.1),
+        );
+        self.position.0 = moved.0;
+        self.position.1 = moved.1;
+        if collided {
+            self.acceleration.0 *= -1.;
+            self.acceleration.1 *= -1.;
+            self.direction += std::f32::consts::PI;
+        }
+
+        self.update_stamina(genotype, sprinting);
+
+        let mut energy_cost =
+            context
+                .metabolism
+                .energy_cost(genotype, vector_length(movement), sprinting)
+                * context.clock.hunger_multiplier();
+        if self.pregnancy.is_some() {
+            energy_cost *= PREGNANCY_HUNGER_MULTIPLIER;
+        }
+        self.hunger -= energy_cost;
         if self.hunger <= 0. {
             self.hunger = 0.;
             self.health -= STARVING_DAMAGE;
         }
         if self.health <= 0. {
-            self.dead = true;
-            self.state = State::Dead;
+            self.die(DeathCause::Starvation);
+        }
+        if self.life >= genotype.max_lifespan {
+            self.die(DeathCause::OldAge);
+        }
+
+        if let Some(pregnancy) = self.pregnancy.clone() {
+            if time >= pregnancy.due {
+                let schema = &context.species.get(self.kind.species()).genes;
+                self.give_birth(
+                    pregnancy,
+                    schema,
+                    time,
+                    neat,
+                    thread_rng,
+                    &mut modified_agents,
+                );
+            }
         }
 
         self.acceleration.0 *= 0.9;
         self.acceleration.1 *= 0.9;
-        match self.kind {
-            AgentType::Sheep(genotype) => {
-                match self.state {
-                    State::Fleeing => {
-                        if self.health <= 0. {
-                            self.dead = true;
-                            self.state = State::Dead;
-                        }
-                        // Get nearby wolves
-                        let mut closest_distance = f32::MAX;
-                        let mut closest_wolf = None;
-                        for id in nearby_agents.iter() {
-                            match agents.get(&id.0).unwrap().kind {
-                                AgentType::Wolf(_) => {
-                                    let pos = agents.get(&id.0).unwrap().position;
-                                    let dist = (pos.0.powf(2.) + pos.1.powf(2.))
-                                        - (self.position.0.powf(2.) + self.position.1.powf(2.));
-                                    if dist < closest_distance {
-                                        closest_distance = dist;
-                                        closest_wolf = Some(id.0);
-                                    }
-                                }
-                                _ => {}
-                            }
-                        }
-                        match closest_wolf {
-                            Some(id) => {
-                                let wolf = agents.get(&id).unwrap();
-                                let wolf_direction = normalize_vector((
-                                    self.position.0 - wolf.position.0,
-                                    self.position.1 - wolf.position.1,
-                                ));
-                                self.acceleration.0 = wolf_direction.0 * genotype.movement_speed;
-                                self.acceleration.1 = wolf_direction.1 * genotype.movement_speed;
-                            }
-                            None => {
-                                self.state = State::Idle;
-                            }
-                        }
-                    }
-                    _ => {}
-                }
-            }
-            AgentType::Wolf(genotype) => {}
-            AgentType::Grass() => {
-                self.health += PLANT_GROWTH_RATE;
-            }
+
+        if self.dead {
+            return modified_agents;
         }
 
-        match self.state {
-            State::Idle => {
-                if let AgentType::Sheep(_) = self.kind {
-                    // Check if nearby wolf
-                    self.wolf_fleeing_check(&nearby_agents, agents)
-                }
-
-                if self.hunger < MIN_HUNGER {
-                    // Get nearby food
-                    let mut closest = Uuid::nil();
-                    let mut closest_distance = f32::MAX;
-                    for nearby_agent in nearby_agents.iter() {
-                        let agent = agents.get(&nearby_agent.0).unwrap();
-                        match self.kind {
-                            AgentType::Sheep(_) => match agent.kind {
-                                AgentType::Grass() => {
-                                    if !agent.dead {
-                                        let distance = ((nearby_agent.1).0.powf(2.)
-                                            + (nearby_agent.1).1.powf(2.))
-                                            - (self.position.0.powf(2.) + self.position.1.powf(2.));
-                                        if distance < closest_distance {
-                                            closest = nearby_agent.0;
-                                            closest_distance = distance;
-                                        }
-                                    }
-                                }
-                                _ => {}
-                            },
-                            AgentType::Wolf(_) => match agent.kind {
-                                AgentType::Sheep(_) => {
-                                    if !agent.dead {
-                                        let distance = ((nearby_agent.1).0.powf(2.)
-                                            + (nearby_agent.1).1.powf(2.))
-                                            - (self.position.0.powf(2.) + self.position.1.powf(2.));
-                                        if distance < closest_distance {
-                                            closest = nearby_agent.0;
-                                            closest_distance = distance;
-                                        }
-                                    }
-                                }
-                                _ => {}
-                            },
-                            _ => {}
-                        }
-                    }
-                    if closest != Uuid::nil() {
-                        self.state = State::Hunting(closest);
-                    }
-                    return modified_agents;
-                } else if genotype.reproduction_chance < thread_rng.gen() {
-                    // If not hungry, try to reproduce
-                    // DONE: Reproduction mechanics
-                    for nearby_agent in nearby_agents.iter() {
-                        let agent = agents.get(&nearby_agent.0).unwrap();
-                        if self.kind.to_int() == agent.kind.to_int() {
-                            if let State::Idle = agent.state {
-                                let distance = ((nearby_agent.1).0.powf(2.)
-                                    + (nearby_agent.1).1.powf(2.))
-                                    - (self.position.0.powf(2.) + self.position.1.powf(2.));
-
-                                if distance < 10. {
-                                    // If close enough, spawn new agent and crossbreed genotypes
-                                    let mut agent = agent.clone();
-
-                                    self.timeout = 1.;
-                                    self.last_time = time;
-                                    agent.timeout = 1.;
-                                    agent.last_time = time;
-
-                                    let mut new_agent = Agent::new(
-                                        self.kind,
-                                        (self.position.0, self.position.1),
-                                        Uuid::new_v4(),
-                                        thread_rng.gen::<f64>() * 10000.,
-                                    );
-                                    let new_genotype = self.kind.genotype().crossbreed(&agent.kind.genotype(), thread_rng);
-                                    new_agent.kind.set_genotype(new_genotype);
-                                    
-                                    modified_agents.insert(new_agent.id, new_agent);
-
-                                    self.state = State::Idle;
-                                    agent.state = State::Idle;
-
-                                    modified_agents.insert(agent.id, agent);
-                                    println!("A new {} was born!", self.kind.to_string());
-                                }
-                            }
-                        }
-                    }
-                }
-
-                // Wander from time to time
-                if time > self.last_time + self.timeout {
-                    self.direction +=
-                        (noise.get([self.seed, (time as f64) * 0.07]) as f32) * 2. - 1.;
-
-                    self.acceleration.0 +=
-                        self.direction.cos() * genotype.movement_speed * WANDER_SPEED;
-                    self.acceleration.1 +=
-                        self.direction.sin() * genotype.movement_speed * WANDER_SPEED;
-                    self.last_time = time;
-                }
-            }
-            State::Hunting(target) => {
-                if let AgentType::Sheep(_) = self.kind {
-                    self.wolf_fleeing_check(&nearby_agents, agents)
-                }
-                // Check if target is still nearby
-                match nearby_agents.iter().find(|a| a.0 == target) {
-                    Some(a) => {
-                        let potential_prey = agents.get(&a.0);
-                        if let None = potential_prey {
-                            // Prey is no longer nearby, go back to idle
-                            self.state = State::Idle;
-                        } else {
-                            // Found prey, continuing predator routine
-                            let mut prey = potential_prey.unwrap().clone();
-                            let prey_direction = normalize_vector((
-                                prey.position.0 - self.position.0,
-                                prey.position.1 - self.position.1,
-                            ));
-                            self.acceleration.0 = prey_direction.0;
-                            self.acceleration.1 = prey_direction.1;
-                            if (prey.position.0 - self.position.0).abs() < 4.
-                                && (prey.position.1 - self.position.1).abs() < 4.
-                            {
-                                self.acceleration.0 = 0.;
-                                self.acceleration.1 = 0.;
-
-                                if time > self.last_time + self.timeout && !prey.dead {
-                                    // Eat prey
-                                    self.hunger += prey.eat(BITE_SIZE);
-                                    if self.hunger > SATIETY {
-                                        self.state = State::Idle;
-                                    }
-                                    if self.hunger > 100. {
-                                        self.hunger = 100.
-                                    }
-                                    self.last_time = time;
-                                    self.timeout = 1.;
-                                }
-                            }
-                            modified_agents.insert(a.0, prey);
-                        }
-                    }
-                    None => {
-                        self.state = State::Idle;
-                    }
-                }
-            }
-            State::Dead => self.dead = true,
-            _ => {}
+        update_infection(self, context, thread_rng);
+        if self.dead {
+            return modified_agents;
+        }
+        update_memory(self, context);
+        update_alarm(self, context, thread_rng);
+        if context.species.hunts_in_packs(&self.kind) {
+            update_pack(self, context);
+        }
+        if context.species.is_territorial(&self.kind) {
+            update_home(self, context);
+        }
+        behaviours.update(self, context, thread_rng, &mut modified_agents);
+        feed_young(self, context, &mut modified_agents);
+
+        // Herd animals keep together on top of whatever they are doing
+        if context.species.flocks(&self.kind) && !matches!(self.state, State::Resting) {
+            let steering = flock(self, context);
+            self.acceleration.0 += steering.0;
+            self.acceleration.1 += steering.1;
         }
 
         /* self.health -= 1.; */
 
-        if self.position.0 >= 1024. {
+        if self.position.0 >= bounds.max_x() {
             self.acceleration.0 *= -1.;
-        } else if self.position.0 <= 0. {
+        } else if self.position.0 <= bounds.x {
             self.acceleration.0 *= -1.;
         }
-        if self.position.1 >= 1024. {
+        if self.position.1 >= bounds.max_y() {
             self.acceleration.1 *= -1.;
-        } else if self.position.1 <= 0. {
+        } else if self.position.1 <= bounds.y {
             self.acceleration.1 *= -1.;
         }
 
         modified_agents
     }
 
-    pub fn get_closest_food(&self, agent_list: Vec<Agent>) -> Uuid {
-        agent_list.iter().find(|a| a.kind.to_int() == 2).unwrap().id
+    fn update_stamina(&mut self, genotype: &Genotype, sprinting: bool) {
+        if sprinting {
+            self.stamina -= SPRINT_DRAIN;
+            if self.stamina <= 0. {
+                self.stamina = 0.;
+                self.exhausted = true;
+            }
+        } else {
+            self.stamina =
+                (self.stamina + genotype.stamina_recovery).min(genotype.stamina_capacity);
+            if self.exhausted && self.stamina >= genotype.stamina_capacity * EXHAUSTION_RECOVERY {
+                self.exhausted = false;
+            }
+        }
     }
 
-    pub fn eat(&mut self, bite: f32) -> f32 {
-        match self.kind {
-            AgentType::Wolf(_) => {
-                panic!("Tried to eat non-food agent {}!", self.kind);
-            }
-            _ => {
-                if self.health - bite < 0. {
-                    self.dead = true;
-                    let bitten = self.health % bite;
-                    self.health = 0.;
-                    return bitten;
-                } else {
-                    self.health -= bite;
-                    return self.health;
-                }
+    pub fn can_mate_with(&self, other: &Agent) -> bool {
+        if self.kind.species() != other.kind.species() || !self.kind.is_animal() {
+            return false;
+        }
+        let available = match other.state {
+            State::Idle => true,
+            State::Reproducing(target) => target == self.id,
+            _ => false,
+        };
+        available
+            && !other.dead
+            && other.sex != self.sex
+            && other.pregnancy.is_none()
+            && other
+                .kind
+                .genotype()
+                .is_some_and(|genotype| genotype.fertility(other.life) > 0.)
+            // Evolving brains only mate within their species, which gives new structure time to
+            // be tuned before it has to compete with the rest
+            && match (&self.neat, &other.neat) {
+                (Some(mine), Some(theirs)) => mine.species == theirs.species,
+                _ => true,
             }
+    }
+
+    // The female conceives and carries the litter until due
+    pub fn mate(&mut self, mate: &mut Agent, genotype: &Genotype, time: f32) {
+        let mate_genotype = match mate.kind.genotype() {
+            Some(mate_genotype) => mate_genotype,
+            None => return,
+        };
+        let pregnancy = match self.sex {
+            Sex::Female => Pregnancy {
+                father: mate_genotype.clone(),
+                father_brain: mate.neat.clone(),
+                father_life: mate.life,
+                conceived: time,
+                due: time + genotype.gestation_duration,
+            },
+            Sex::Male => Pregnancy {
+                father: genotype.clone(),
+                father_brain: self.neat.clone(),
+                father_life: self.life,
+                conceived: time,
+                due: time + mate_genotype.gestation_duration,
+            },
+        };
+        mate.timeout = 1.;
+        mate.last_time = time;
+
+        match self.sex {
+            Sex::Female => self.pregnancy = Some(pregnancy),
+            Sex::Male => mate.pregnancy = Some(pregnancy),
         }
+        mate.state = State::Idle;
     }
 
-    pub fn wolf_fleeing_check(
+    fn give_birth(
         &mut self,
-        nearby_agents: &Vec<(Uuid, (f32, f32))>,
-        agents: &HashMap<Uuid, Agent>,
+        pregnancy: Pregnancy,
+        schema: &GeneSchema,
+        time: f32,
+        mut neat: Option<&mut Neat>,
+        thread_rng: &mut ThreadRng,
+        modified_agents: &mut HashMap<Uuid, Agent>,
     ) {
-        for nearby_agent in nearby_agents.iter() {
-            match agents.get(&nearby_agent.0).unwrap().kind {
-                AgentType::Wolf(_) => {
-                    self.state = State::Fleeing;
-                }
-                _ => {}
+        let mother = match self.kind.genotype() {
+            Some(mother) => mother,
+            None => return,
+        };
+        for _ in 0..mother.litter_count() {
+            // Crossbreed genotypes separately for each offspring of the litter
+            let mut new_agent = Agent::new(
+                self.kind.clone(),
+                (self.position.0, self.position.1),
+                Uuid::new_v4(),
+                thread_rng.gen::<f64>() * 10000.,
+                time,
+            );
+            let new_genotype = mother.crossbreed(&pregnancy.father, schema, thread_rng);
+            // Newborns start small and frail
+            new_agent.health = new_genotype.health_scale * new_genotype.growth(0.);
+            new_agent.kind.set_genotype(new_genotype);
+            new_agent.parent = Some(self.id);
+            // The parent that survived longer passes on its extra structure
+            if let (Some(neat), Some(mother_brain), Some(father_brain)) =
+                (neat.as_deref_mut(), &self.neat, &pregnancy.father_brain)
+            {
+                new_agent.neat = Some(neat.offspring(
+                    mother_brain,
+                    father_brain,
+                    self.life >= pregnancy.father_life,
+                    thread_rng,
+                ));
             }
+
+            modified_agents.insert(new_agent.id, new_agent);
         }
+        self.pregnancy = None;
+    }
+
+    pub fn get_closest_food(&self, agent_list: Vec<Agent>) -> Uuid {
+        agent_list.iter().find(|a| a.kind.to_int() == 2).unwrap().id
     }
-}
 
+    // Takes a bite out of this agent, returning how much was actually eaten
+    pub fn eat(&mut self, bite: f32) -> f32 {
+        if self.health <= 0. {
+            return 0.;
+        }
+        if self.health - bite <= 0. {
+            let bitten = self.health;
+            self.health = 0.;
+            // Carcasses simply run out, animals and plants die
+            match self.kind {
+                AgentType::Carcass() => {}
+                _ => self.die(DeathCause::Predation),
+            }
+            bitten
+        } else {
+            self.health -= bite;
+            bite
+        }
+    }
+
+    // Keeps the first recorded cause if the agent was already dying
+    pub fn die(&mut self, cause: DeathCause) {
+        if self.dead {
+            return;
+        }
+        self.dead = true;
+        self.state = State::Dead;
+        self.death_cause = Some(cause);
+        self.pregnancy = None;
+    }
+
+    // Turns a dead animal into a carcass that can be scavenged until it rots
+    pub fn become_carcass(&mut self) {
+        let biomass = match self.kind.genotype() {
+            Some(genotype) => {
+                genotype.health_scale * genotype.growth(self.life) * CARCASS_BIOMASS_SCALE
+            }
+            None => return,
+        };
+        self.kind = AgentType::Carcass();
+        self.health = biomass;
+        self.hunger = 0.;
+        self.acceleration = (0., 0.);
+        self.dead = false;
+        self.state = State::Idle;
+        self.pregnancy = None;
+        self.neat = None;
+        self.infections.clear();
+        self.memory = SpatialMemory::new();
+        self.alarm = None;
+        // Carcasses aren't alive for their pack, den or young
+        self.pack = None;
+        self.home = None;
+        self.parent = None;
+    }
+}
@@ -0,0 +1,849 @@
+use std::{collections::HashMap, f32::consts::PI};
+
+use noise::{NoiseFn, OpenSimplex};
+use rand::rngs::ThreadRng;
+use uuid::Uuid;
+
+use crate::{
+    agent::{Agent, DeathCause, State},
+    brain::{BrainMode, Think},
+    care::FollowParent,
+    disease::Pathogen,
+    environment::Clock,
+    fields::{self, Fields},
+    genes::{Genotype, JUVENILE_SIZE},
+    metabolism::Metabolism,
+    neat::Evolve,
+    pack,
+    signals::{self, CALLER_CONSPICUOUSNESS},
+    species::SpeciesRegistry,
+    terrain::Terrain,
+    territory::{Defend, ReturnHome},
+    vector_length,
+};
+
+// Bonus given to the running behaviour so agents don't dither between two close options
+const COMMITMENT_BONUS: f32 = 0.1;
+
+const WANDER_SCORE: f32 = 0.1;
+const REST_SCORE: f32 = 0.4;
+const EXHAUSTED_REST_SCORE: f32 = 0.6;
+const FLEE_SCORE: f32 = 1.;
+// Share of the flee score an alarm call is worth compared to seeing the predator
+const HEARD_ALARM_URGENCY: f32 = 0.8;
+const MATE_SCORE: f32 = 0.5;
+
+const WANDER_SPEED: f32 = 2.;
+const MIN_HUNGER: f32 = 30.;
+const SATIETY: f32 = 40.;
+const BITE_SIZE: f32 = 10.;
+const BITE_DISTANCE: f32 = 4.;
+const MATING_DISTANCE: f32 = 10.;
+// Health lost per bite of a fully toxic plant without any tolerance
+const TOXIN_DAMAGE: f32 = 2.;
+// How much a fully selective forager favours its preferred food over the nearest one
+const MAX_SELECTIVITY: f32 = 3.;
+// Extra appeal of a newborn to predators over a grown adult, fading as it grows
+const JUVENILE_VULNERABILITY: f32 = 2.;
+
+// Flockmates further than this are ignored, closer than the separation distance are avoided
+const FLOCK_RADIUS: f32 = 60.;
+const SEPARATION_DISTANCE: f32 = 12.;
+// Strength of the combined flocking steering relative to the movement speed
+const FLOCKING_STRENGTH: f32 = 0.5;
+// Share of the turn towards what the agent remembers taken each time it changes direction
+const MEMORY_PULL: f32 = 0.3;
+// Same for the scent and fear fields
+const FIELD_PULL: f32 = 0.3;
+
+// Everything a behaviour is allowed to look at while scoring or acting
+pub struct Context<'a> {
+    pub nearby_agents: &'a Vec<(Uuid, (f32, f32))>,
+    pub agents: &'a HashMap<Uuid, Agent>,
+    pub genotype: Genotype,
+    pub noise: &'a OpenSimplex,
+    pub terrain: &'a Terrain,
+    pub clock: &'a Clock,
+    pub metabolism: &'a Metabolism,
+    pub species: &'a SpeciesRegistry,
+    pub pathogens: &'a [Pathogen],
+    pub fields: &'a Fields,
+    pub time: f32,
+}
+
+impl<'a> Context<'a> {
+    // Nearby agents resolved from the agent store, skipping the agent itself
+    pub fn neighbours(&self, agent: &Agent) -> Vec<&'a Agent> {
+        self.nearby_agents
+            .iter()
+            .filter(|a| a.0 != agent.id)
+            .filter_map(|a| self.agents.get(&a.0))
+            .collect()
+    }
+
+    pub fn get_nearby(&self, id: Uuid) -> Option<&'a Agent> {
+        self.nearby_agents
+            .iter()
+            .find(|a| a.0 == id)
+            .and_then(|a| self.agents.get(&a.0))
+    }
+}
+
+pub trait Behaviour {
+    fn name(&self) -> &'static str;
+    // Whether this behaviour is the one currently driving the agent's state
+    fn is_running(&self, agent: &Agent, context: &Context) -> bool;
+    // How much the agent wants to do this right now, 0 or less means it can't
+    fn score(&self, agent: &Agent, context: &Context) -> f32;
+    fn act(
+        &self,
+        agent: &mut Agent,
+        context: &Context,
+        thread_rng: &mut ThreadRng,
+        modified_agents: &mut HashMap<Uuid, Agent>,
+    );
+}
+
+pub struct BehaviourSet {
+    behaviours: Vec<Box<dyn Behaviour>>,
+}
+
+impl BehaviourSet {
+    pub fn new() -> BehaviourSet {
+        BehaviourSet {
+            behaviours: vec![
+                Box::new(Wander),
+                Box::new(Rest),
+                Box::new(Flee),
+                Box::new(Hunt),
+                Box::new(Graze),
+                Box::new(Mate),
+                Box::new(ReturnHome),
+                Box::new(Defend),
+                Box::new(FollowParent),
+            ],
+        }
+    }
+
+    pub fn for_mode(mode: BrainMode) -> BehaviourSet {
+        match mode {
+            BrainMode::Scripted => BehaviourSet::new(),
+            BrainMode::Fixed => BehaviourSet::neural(),
+            BrainMode::Neat => BehaviourSet::evolving(),
+        }
+    }
+
+    // Brain mode, the evolved neural network makes every decision
+    pub fn neural() -> BehaviourSet {
+        BehaviourSet {
+            behaviours: vec![Box::new(Think)],
+        }
+    }
+
+    // Same as the neural mode, but the network topology evolves as well
+    pub fn evolving() -> BehaviourSet {
+        BehaviourSet {
+            behaviours: vec![Box::new(Evolve)],
+        }
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.behaviours.iter().map(|b| b.name()).collect()
+    }
+
+    pub fn choose(&self, agent: &Agent, context: &Context) -> Option<&dyn Behaviour> {
+        let mut best = None;
+        let mut best_score = 0.;
+        for behaviour in self.behaviours.iter() {
+            let mut score = behaviour.score(agent, context);
+            if score <= 0. {
+                continue;
+            }
+            if behaviour.is_running(agent, context) {
+                score += COMMITMENT_BONUS;
+            }
+            if score > best_score {
+                best_score = score;
+                best = Some(behaviour.as_ref());
+            }
+        }
+        best
+    }
+
+    pub fn update(
+        &self,
+        agent: &mut Agent,
+        context: &Context,
+        thread_rng: &mut ThreadRng,
+        modified_agents: &mut HashMap<Uuid, Agent>,
+    ) {
+        match self.choose(agent, context) {
+            Some(behaviour) => behaviour.act(agent, context, thread_rng, modified_agents),
+            None => agent.state = State::Idle,
+        }
+    }
+}
+
+pub fn distance_squared(a: &Agent, b: &Agent) -> f32 {
+    (a.position.0 - b.position.0).powi(2) + (a.position.1 - b.position.1).powi(2)
+}
+
+pub fn normalize(vector: (f32, f32)) -> (f32, f32) {
+    let length = vector_length(vector);
+    if length <= f32::EPSILON {
+        return (0., 0.);
+    }
+    (vector.0 / length, vector.1 / length)
+}
+
+// Boids steering from nearby animals of the same kind, weighted by the flocking genes
+pub fn flock(agent: &Agent, context: &Context) -> (f32, f32) {
+    let genotype = &context.genotype;
+    let mut separation = (0., 0.);
+    let mut heading = (0., 0.);
+    let mut centre = (0., 0.);
+    let mut flockmates = 0;
+    for other in context.neighbours(agent) {
+        if other.dead || other.kind.species() != agent.kind.species() {
+            continue;
+        }
+        let distance = distance_squared(agent, other).sqrt();
+        if distance > FLOCK_RADIUS {
+            continue;
+        }
+        if distance < SEPARATION_DISTANCE {
+            // Push away harder from the ones that are closest
+            let strength = 1. - distance / SEPARATION_DISTANCE;
+            let away = normalize((
+                agent.position.0 - other.position.0,
+                agent.position.1 - other.position.1,
+            ));
+            separation.0 += away.0 * strength;
+            separation.1 += away.1 * strength;
+        }
+        heading.0 += other.acceleration.0;
+        heading.1 += other.acceleration.1;
+        centre.0 += other.position.0;
+        centre.1 += other.position.1;
+        flockmates += 1;
+    }
+    if flockmates == 0 {
+        return (0., 0.);
+    }
+
+    let separation = normalize(separation);
+    let alignment = normalize(heading);
+    let cohesion = normalize((
+        centre.0 / flockmates as f32 - agent.position.0,
+        centre.1 / flockmates as f32 - agent.position.1,
+    ));
+    let speed = genotype.movement_speed * FLOCKING_STRENGTH;
+    (
+        (separation.0 * genotype.separation
+            + alignment.0 * genotype.alignment
+            + cohesion.0 * genotype.cohesion)
+            * speed,
+        (separation.1 * genotype.separation
+            + alignment.1 * genotype.alignment
+            + cohesion.1 * genotype.cohesion)
+            * speed,
+    )
+}
+
+// Sets the acceleration towards a point, going around obstacles
+pub fn head_towards(agent: &mut Agent, context: &Context, target: (f32, f32), speed: f32) {
+    let direction = context
+        .terrain
+        .steer((agent.position.0, agent.position.1), target);
+    agent.acceleration.0 = direction.0 * speed;
+    agent.acceleration.1 = direction.1 * speed;
+}
+
+// Turns the agent's heading a share of the way towards a direction
+fn turn_towards(agent: &mut Agent, direction: (f32, f32), share: f32) {
+    let turn = (direction.1.atan2(direction.0) - agent.direction + PI).rem_euclid(2. * PI) - PI;
+    agent.direction += turn * share;
+}
+
+pub struct Wander;
+impl Behaviour for Wander {
+    fn name(&self) -> &'static str {
+        "Wander"
+    }
+
+    fn is_running(&self, agent: &Agent, _context: &Context) -> bool {
+        matches!(agent.state, State::Idle)
+    }
+
+    fn score(&self, _agent: &Agent, context: &Context) -> f32 {
+        WANDER_SCORE * context.genotype.curiosity
+    }
+
+    fn act(
+        &self,
+        agent: &mut Agent,
+        context: &Context,
+        _thread_rng: &mut ThreadRng,
+        _modified_agents: &mut HashMap<Uuid, Agent>,
+    ) {
+        agent.state = State::Idle;
+        // Wander from time to time
+        if context.time > agent.last_time + agent.timeout {
+            agent.direction += (context
+                .noise
+                .get([agent.seed, (context.time as f64) * 0.07])
+                as f32)
+                * 2.
+                - 1.;
+
+            // Turn part of the way towards remembered food and away from remembered danger
+            if let Some(pull) = agent
+                .memory
+                .wander_direction((agent.position.0, agent.position.1), agent.hunger)
+            {
+                turn_towards(agent, pull, MEMORY_PULL);
+            }
+            // Follow prey scent and keep away from kill sites
+            if let Some(pull) = fields::wander_direction(agent, context) {
+                turn_towards(agent, pull, FIELD_PULL);
+            }
+
+            let wander_speed = context.genotype.movement_speed
+                * WANDER_SPEED
+                * context.clock.activity_multiplier();
+            agent.acceleration.0 += agent.direction.cos() * wander_speed;
+            agent.acceleration.1 += agent.direction.sin() * wander_speed;
+            agent.last_time = context.time;
+        }
+    }
+}
+
+pub struct Rest;
+impl Behaviour for Rest {
+    fn name(&self) -> &'static str {
+        "Rest"
+    }
+
+    fn is_running(&self, agent: &Agent, _context: &Context) -> bool {
+        matches!(agent.state, State::Resting)
+    }
+
+    fn score(&self, agent: &Agent, context: &Context) -> f32 {
+        let mut score = REST_SCORE * (1. - context.clock.daylight());
+        if agent.exhausted {
+            score = score.max(EXHAUSTED_REST_SCORE);
+        }
+        score * context.genotype.laziness
+    }
+
+    fn act(
+        &self,
+        agent: &mut Agent,
+        _context: &Context,
+        _thread_rng: &mut ThreadRng,
+        _modified_agents: &mut HashMap<Uuid, Agent>,
+    ) {
+        agent.state = State::Resting;
+        agent.acceleration.0 *= 0.5;
+        agent.acceleration.1 *= 0.5;
+    }
+}
+
+pub fn closest_threat<'a>(agent: &Agent, context: &Context<'a>) -> Option<&'a Agent> {
+    let mut closest_distance = f32::MAX;
+    let mut closest = None;
+    for other in context.neighbours(agent) {
+        if other.dead || !context.species.fears(&agent.kind, &other.kind) {
+            continue;
+        }
+        let distance = distance_squared(agent, other);
+        if distance < closest_distance {
+            closest_distance = distance;
+            closest = Some(other);
+        }
+    }
+    closest
+}
+
+pub struct Flee;
+impl Flee {
+    // Where the danger is and how seriously to take it, a predator in sight beats an alarm call
+    fn threat(&self, agent: &Agent, context: &Context) -> Option<((f32, f32), f32)> {
+        if let Some(threat) = closest_threat(agent, context) {
+            return Some(((threat.position.0, threat.position.1), 1.));
+        }
+        signals::heard_alarm(agent, context).map(|threat| (threat, HEARD_ALARM_URGENCY))
+    }
+}
+impl Behaviour for Flee {
+    fn name(&self) -> &'static str {
+        "Flee"
+    }
+
+    fn is_running(&self, agent: &Agent, _context: &Context) -> bool {
+        matches!(agent.state, State::Fleeing)
+    }
+
+    fn score(&self, agent: &Agent, context: &Context) -> f32 {
+        match self.threat(agent, context) {
+            Some((threat, urgency)) => {
+                let distance =
+                    vector_length((threat.0 - agent.position.0, threat.1 - agent.position.1));
+                let proximity = 1. - (distance / context.genotype.sight_distance).min(1.);
+                (FLEE_SCORE + proximity) * context.genotype.fear * urgency
+            }
+            None => 0.,
+        }
+    }
+
+    fn act(
+        &self,
+        agent: &mut Agent,
+        context: &Context,
+        _thread_rng: &mut ThreadRng,
+        _modified_agents: &mut HashMap<Uuid, Agent>,
+    ) {
+        match self.threat(agent, context) {
+            Some((threat, _)) => {
+                agent.state = State::Fleeing;
+                let direction = context.terrain.flee(
+                    (agent.position.0, agent.position.1),
+                    threat,
+                    context.genotype.sight_distance,
+                );
+                let top_speed =
+                    context.genotype.movement_speed * context.genotype.sprint_multiplier;
+                agent.acceleration.0 = direction.0 * top_speed;
+                agent.acceleration.1 = direction.1 * top_speed;
+            }
+            None => agent.state = State::Idle,
+        }
+    }
+}
+
+// How much easier an animal is to bring down than a grown adult of its kind, predators single
+// out the young
+pub fn vulnerability(prey: &Agent) -> f32 {
+    match prey.kind.genotype() {
+        Some(genotype) => {
+            let grown = (genotype.growth(prey.life) - JUVENILE_SIZE) / (1. - JUVENILE_SIZE);
+            1. + JUVENILE_VULNERABILITY * (1. - grown.clamp(0., 1.))
+        }
+        None => 1.,
+    }
+}
+
+// Shared routine for Hunt and Graze, which only differ in which food they go after
+fn find_food<'a>(agent: &Agent, context: &Context<'a>, animals: bool) -> Option<&'a Agent> {
+    let desirability = |food: &Agent| {
+        context
+            .species
+            .desirability(&agent.kind, &context.genotype, &food.kind)
+    };
+    // Predators still handling their last kill leave live prey alone
+    let handling = context.time < agent.handling_until;
+    let is_food = |food: &Agent| {
+        !food.dead
+            && desirability(food) > 0.
+            && food.kind.is_plant() != animals
+            && !(handling && food.kind.is_animal())
+    };
+    // Stick to the current target while it's still around
+    if let State::Hunting(target) = agent.state {
+        if let Some(food) = context.get_nearby(target) {
+            if is_food(food) {
+                return Some(food);
+            }
+        }
+    }
+    // Pack members go after whatever the rest of the pack is hunting or eating
+    if animals {
+        if let Some(food) = pack::shared_target(agent, context) {
+            if is_food(food) {
+                return Some(food);
+            }
+        }
+    }
+    // New live prey is only noticed within the search radius of the functional response
+    let prey_in_sight = context
+        .neighbours(agent)
+        .iter()
+        .filter(|food| food.kind.is_animal() && is_food(food))
+        .count();
+    let search_radius = context
+        .species
+        .get(agent.kind.species())
+        .response
+        .search_radius(
+            context.genotype.sight_distance * context.clock.sight_multiplier(),
+            prey_in_sight,
+        );
+    // Weigh how much the food is wanted against how far it is, selective foragers travel further
+    let exponent = 1. + context.genotype.selectivity.max(0.) * MAX_SELECTIVITY;
+    let mut best_score = 0.;
+    let mut best = None;
+    for other in context.neighbours(agent) {
+        if !is_food(other) {
+            continue;
+        }
+        // Prey making an alarm call gives itself away
+        let calling = signals::is_calling(other, context.time);
+        if other.kind.is_animal()
+            && !calling
+            && distance_squared(agent, other) > search_radius.powi(2)
+        {
+            continue;
+        }
+        let distance = distance_squared(agent, other).sqrt().max(1.);
+        let mut score = desirability(other).powf(exponent) / distance;
+        if other.kind.is_animal() {
+            score *= vulnerability(other);
+        }
+        if calling {
+            score *= CALLER_CONSPICUOUSNESS;
+        }
+        if score > best_score {
+            best_score = score;
+            best = Some(other);
+        }
+    }
+    best
+}
+
+// Most desirable kind of food in sight, used to weigh hunting against grazing
+fn best_desirability(agent: &Agent, context: &Context, animals: bool) -> f32 {
+    context
+        .neighbours(agent)
+        .iter()
+        .filter(|food| !food.dead && food.kind.is_plant() != animals)
+        .map(|food| {
+            context
+                .species
+                .desirability(&agent.kind, &context.genotype, &food.kind)
+        })
+        .fold(0., f32::max)
+}
+
+// Hunger at which foraging stops, predators follow their functional response
+fn satiety(agent: &Agent, context: &Context, animals: bool) -> f32 {
+    if animals {
+        context.species.get(agent.kind.species()).response.satiation
+    } else {
+        SATIETY
+    }
+}
+
+fn forage_score(agent: &Agent, context: &Context, animals: bool, running: bool) -> f32 {
+    let hungry =
+        agent.hunger < MIN_HUNGER || (running && agent.hunger < satiety(agent, context, animals));
+    if !hungry || find_food(agent, context, animals).is_none() {
+        return 0.;
+    }
+    (1. - agent.hunger / 100.)
+        * context.genotype.appetite
+        * best_desirability(agent, context, animals)
+}
+
+fn forage_act(
+    agent: &mut Agent,
+    context: &Context,
+    animals: bool,
+    modified_agents: &mut HashMap<Uuid, Agent>,
+) {
+    let food = match find_food(agent, context, animals) {
+        Some(food) => food,
+        None => {
+            agent.state = State::Idle;
+            return;
+        }
+    };
+    agent.state = State::Hunting(food.id);
+
+    let mut food = food.clone();
+    let top_speed = context.genotype.movement_speed * context.genotype.sprint_multiplier;
+    let target = pack::approach_point(agent, context, &food);
+    head_towards(agent, context, target, top_speed);
+    if bite(agent, &mut food, context, modified_agents)
+        && agent.hunger > satiety(agent, context, animals)
+    {
+        agent.state = State::Idle;
+    }
+    modified_agents.insert(food.id, food);
+}
+
+// Stops and takes a bite out of the food if it's within reach, returns whether it was
+pub fn bite(
+    agent: &mut Agent,
+    food: &mut Agent,
+    context: &Context,
+    modified_agents: &mut HashMap<Uuid, Agent>,
+) -> bool {
+    if (food.position.0 - agent.position.0).abs() >= BITE_DISTANCE
+        || (food.position.1 - agent.position.1).abs() >= BITE_DISTANCE
+    {
+        return false;
+    }
+    agent.acceleration.0 = 0.;
+    agent.acceleration.1 = 0.;
+
+    if context.time > agent.last_time + agent.timeout {
+        let was_alive = food.kind.is_animal() && !food.dead;
+        let bitten = food.eat(BITE_SIZE);
+        let mut energy = bitten
+            * context.metabolism.food_efficiency(&food.kind)
+            * context
+                .species
+                .digestion(&agent.kind, &context.genotype, &food.kind);
+        if let Some(plant) = food.kind.plant_genotype() {
+            energy *= plant.nutrition;
+            // Whatever the toxins exceed the tolerance by hurts
+            agent.health -=
+                bitten * (plant.toxicity - context.genotype.toxin_tolerance).max(0.) * TOXIN_DAMAGE;
+            if agent.health <= 0. {
+                agent.die(DeathCause::Poisoning);
+            }
+        }
+        agent.hunger += energy;
+        if was_alive && food.dead {
+            agent.kills += 1;
+            pack::share_kill(agent, food, energy, context, modified_agents);
+            agent.handling_until = context.time
+                + context
+                    .species
+                    .get(agent.kind.species())
+                    .response
+                    .handling_time();
+        }
+        if agent.hunger > 100. {
+            agent.hunger = 100.
+        }
+        agent.last_time = context.time;
+        agent.timeout = 1.;
+    }
+    true
+}
+
+// Mates with a partner within reach, who gets a say too and can reject us
+pub fn court(agent: &mut Agent, mate: &mut Agent, context: &Context) -> bool {
+    if vector_length((
+        mate.position.0 - agent.position.0,
+        mate.position.1 - agent.position.1,
+    )) >= MATING_DISTANCE
+    {
+        return false;
+    }
+    let schema = &context.species.get(agent.kind.species()).genes;
+    if mate
+        .kind
+        .genotype()
+        .is_some_and(|genes| genes.accepts(&context.genotype, schema))
+    {
+        agent.mate(mate, &context.genotype, context.time);
+    }
+    agent.timeout = 1.;
+    agent.last_time = context.time;
+    agent.state = State::Idle;
+    true
+}
+
+fn is_foraging(agent: &Agent, context: &Context, animals: bool) -> bool {
+    match agent.state {
+        State::Hunting(target) => context
+            .agents
+            .get(&target)
+            .is_some_and(|food| food.kind.is_plant() != animals),
+        _ => false,
+    }
+}
+
+pub struct Hunt;
+impl Behaviour for Hunt {
+    fn name(&self) -> &'static str {
+        "Hunt"
+    }
+
+    fn is_running(&self, agent: &Agent, context: &Context) -> bool {
+        is_foraging(agent, context, true)
+    }
+
+    fn score(&self, agent: &Agent, context: &Context) -> f32 {
+        forage_score(agent, context, true, self.is_running(agent, context))
+    }
+
+    fn act(
+        &self,
+        agent: &mut Agent,
+        context: &Context,
+        _thread_rng: &mut ThreadRng,
+        modified_agents: &mut HashMap<Uuid, Agent>,
+    ) {
+        forage_act(agent, context, true, modified_agents);
+    }
+}
+
+pub struct Graze;
+impl Behaviour for Graze {
+    fn name(&self) -> &'static str {
+        "Graze"
+    }
+
+    fn is_running(&self, agent: &Agent, context: &Context) -> bool {
+        is_foraging(agent, context, false)
+    }
+
+    fn score(&self, agent: &Agent, context: &Context) -> f32 {
+        forage_score(agent, context, false, self.is_running(agent, context))
+    }
+
+    fn act(
+        &self,
+        agent: &mut Agent,
+        context: &Context,
+        _thread_rng: &mut ThreadRng,
+        modified_agents: &mut HashMap<Uuid, Agent>,
+    ) {
+        forage_act(agent, context, false, modified_agents);
+    }
+}
+
+pub struct Mate;
+impl Mate {
+    // The current partner if still available, otherwise the most attractive acceptable one
+    fn find_mate<'a>(&self, agent: &Agent, context: &Context<'a>) -> Option<&'a Agent> {
+        if let State::Reproducing(target) = agent.state {
+            if let Some(mate) = context.get_nearby(target) {
+                if agent.can_mate_with(mate) {
+                    return Some(mate);
+                }
+            }
+        }
+        let schema = &context.species.get(agent.kind.species()).genes;
+        let mut best_mate = None;
+        let mut best_score = f32::MIN;
+        for other in context.neighbours(agent) {
+            if let (true, Some(genes)) = (agent.can_mate_with(other), other.kind.genotype()) {
+                let score = context.genotype.attractiveness(genes, schema);
+                if score > best_score {
+                    best_score = score;
+                    best_mate = Some(other);
+                }
+            }
+        }
+        best_mate.filter(|mate| {
+            mate.kind
+                .genotype()
+                .is_some_and(|genes| context.genotype.accepts(genes, schema))
+        })
+    }
+}
+impl Behaviour for Mate {
+    fn name(&self) -> &'static str {
+        "Mate"
+    }
+
+    fn is_running(&self, agent: &Agent, _context: &Context) -> bool {
+        matches!(agent.state, State::Reproducing(_))
+    }
+
+    fn score(&self, agent: &Agent, context: &Context) -> f32 {
+        let fertility = context.genotype.fertility(agent.life);
+        if agent.pregnancy.is_some() || agent.hunger < MIN_HUNGER || fertility <= 0. {
+            return 0.;
+        }
+        if self.find_mate(agent, context).is_none() {
+            return 0.;
+        }
+        MATE_SCORE * context.genotype.reproduction_chance * fertility * context.genotype.libido
+    }
+
+    fn act(
+        &self,
+        agent: &mut Agent,
+        context: &Context,
+        _thread_rng: &mut ThreadRng,
+        modified_agents: &mut HashMap<Uuid, Agent>,
+    ) {
+        let mate = match self.find_mate(agent, context) {
+            Some(mate) => mate,
+            None => {
+                agent.state = State::Idle;
+                return;
+            }
+        };
+        agent.state = State::Reproducing(mate.id);
+        head_towards(
+            agent,
+            context,
+            (mate.position.0, mate.position.1),
+            context.genotype.movement_speed,
+        );
+
+        let mut mate = mate.clone();
+        if court(agent, &mut mate, context) {
+            modified_agents.insert(mate.id, mate);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        agent::AgentType,
+        species::{SHEEP, WOLF},
+        Bounds,
+    };
+
+    fn animal(species: usize, position: (f32, f32), thread_rng: &mut ThreadRng) -> Agent {
+        let kind = AgentType::Animal(species, Genotype::new(thread_rng));
+        Agent::new(kind, position, Uuid::new_v4(), 0., 0.)
+    }
+
+    fn grown_up(mut agent: Agent) -> Agent {
+        agent.life = agent.kind.genotype().unwrap().maturity_age + 1.;
+        agent
+    }
+
+    #[test]
+    fn newborns_are_the_most_vulnerable() {
+        let mut thread_rng = rand::thread_rng();
+        let newborn = animal(SHEEP, (0., 0.), &mut thread_rng);
+        let adult = grown_up(animal(SHEEP, (0., 0.), &mut thread_rng));
+        assert!((vulnerability(&newborn) - (1. + JUVENILE_VULNERABILITY)).abs() < 1e-5);
+        assert!((vulnerability(&adult) - 1.).abs() < 1e-5);
+    }
+
+    #[test]
+    fn predators_pick_a_juvenile_over_a_slightly_closer_adult() {
+        let mut thread_rng = rand::thread_rng();
+        let bounds = Bounds::new((-100., -100.), 200., 200.);
+        let species = SpeciesRegistry::new(2, 1, 0);
+        let wolf = grown_up(animal(WOLF, (0., 0.), &mut thread_rng));
+        let adult = grown_up(animal(SHEEP, (10., 0.), &mut thread_rng));
+        let newborn = animal(SHEEP, (12., 0.), &mut thread_rng);
+        let nearby_agents = vec![(adult.id, (10., 0.)), (newborn.id, (12., 0.))];
+        let mut genotype = wolf.kind.genotype().unwrap().clone();
+        genotype.sight_distance = 100.;
+        genotype.selectivity = 0.;
+        let mut agents = HashMap::new();
+        for agent in [wolf.clone(), adult, newborn.clone()] {
+            agents.insert(agent.id, agent);
+        }
+        let context = Context {
+            nearby_agents: &nearby_agents,
+            agents: &agents,
+            genotype,
+            noise: &OpenSimplex::new(0),
+            terrain: &Terrain::new(bounds),
+            clock: &Clock::new(),
+            metabolism: &Metabolism::new(),
+            species: &species,
+            pathogens: &[],
+            fields: &Fields::new(bounds, 4),
+            time: 0.,
+        };
+
+        let target = find_food(&wolf, &context, true).map(|prey| prey.id);
+        assert_eq!(target, Some(newborn.id));
+    }
+}
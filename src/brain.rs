@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+
+use rand::{rngs::ThreadRng, Rng};
+use uuid::Uuid;
+
+use crate::{
+    agent::{Agent, State},
+    behaviour::{bite, court, distance_squared, Behaviour, Context},
+};
+
+// Nearest food, threat and mate vectors, hunger, health, stamina and daylight
+pub const BRAIN_INPUTS: usize = 10;
+pub const BRAIN_HIDDEN: usize = 8;
+// Steering x and y, eat and mate drives
+pub const BRAIN_OUTPUTS: usize = 4;
+// Every layer gets an extra bias weight per neuron
+pub const BRAIN_SIZE: usize =
+    (BRAIN_INPUTS + 1) * BRAIN_HIDDEN + (BRAIN_HIDDEN + 1) * BRAIN_OUTPUTS;
+
+const WEIGHT_RANGE: std::ops::Range<f32> = -1.0..1.0;
+// Chance for each weight to be nudged on mutation, and by how much at most
+const WEIGHT_MUTATION_CHANCE: f32 = 0.1;
+const WEIGHT_MUTATION_SIZE: f32 = 0.3;
+
+// Boxed so genotypes, and the agent kinds holding them, stay small
+pub type BrainWeights = Box<[f32; BRAIN_SIZE]>;
+
+// Who makes the decisions, the hand written behaviours, a fixed network or an evolving topology
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BrainMode {
+    Scripted,
+    Fixed,
+    Neat,
+}
+impl BrainMode {
+    pub fn from_int(mode: u8) -> BrainMode {
+        match mode {
+            1 => BrainMode::Fixed,
+            2 => BrainMode::Neat,
+            _ => BrainMode::Scripted,
+        }
+    }
+
+    pub fn to_int(self) -> u8 {
+        match self {
+            BrainMode::Scripted => 0,
+            BrainMode::Fixed => 1,
+            BrainMode::Neat => 2,
+        }
+    }
+}
+
+pub fn random_weights(thread_rng: &mut ThreadRng) -> BrainWeights {
+    let mut weights = [0.; BRAIN_SIZE];
+    for weight in weights.iter_mut() {
+        *weight = thread_rng.gen_range(WEIGHT_RANGE);
+    }
+    Box::new(weights)
+}
+
+// Uniform crossover, each weight is picked from either parent
+pub fn crossover(a: &BrainWeights, b: &BrainWeights, thread_rng: &mut ThreadRng) -> BrainWeights {
+    let mut weights = [0.; BRAIN_SIZE];
+    for i in 0..BRAIN_SIZE {
+        weights[i] = if thread_rng.gen_bool(0.5) { a[i] } else { b[i] };
+    }
+    Box::new(weights)
+}
+
+pub fn mutate(weights: &mut BrainWeights, thread_rng: &mut ThreadRng) {
+    for weight in weights.iter_mut() {
+        if thread_rng.gen::<f32>() < WEIGHT_MUTATION_CHANCE {
+            *weight += (thread_rng.gen::<f32>() * 2. - 1.) * WEIGHT_MUTATION_SIZE;
+        }
+    }
+}
+
+// Feeds the inputs through the hidden layer, all activations are tanh
+pub fn think(weights: &BrainWeights, inputs: &[f32; BRAIN_INPUTS]) -> [f32; BRAIN_OUTPUTS] {
+    let mut hidden = [0.; BRAIN_HIDDEN];
+    let mut offset = 0;
+    for neuron in hidden.iter_mut() {
+        let mut sum = weights[offset + BRAIN_INPUTS];
+        for i in 0..BRAIN_INPUTS {
+            sum += weights[offset + i] * inputs[i];
+        }
+        *neuron = sum.tanh();
+        offset += BRAIN_INPUTS + 1;
+    }
+
+    let mut outputs = [0.; BRAIN_OUTPUTS];
+    for neuron in outputs.iter_mut() {
+        let mut sum = weights[offset + BRAIN_HIDDEN];
+        for i in 0..BRAIN_HIDDEN {
+            sum += weights[offset + i] * hidden[i];
+        }
+        *neuron = sum.tanh();
+        offset += BRAIN_HIDDEN + 1;
+    }
+    outputs
+}
+
+// Nearest agent matching the filter, along with its direction scaled by sight distance
+fn nearest<'a, F: Fn(&Agent) -> bool>(
+    agent: &Agent,
+    context: &Context<'a>,
+    filter: F,
+) -> (Option<&'a Agent>, (f32, f32)) {
+    let mut closest_distance = f32::MAX;
+    let mut closest = None;
+    for other in context.neighbours(agent) {
+        if !filter(other) {
+            continue;
+        }
+        let distance = distance_squared(agent, other);
+        if distance < closest_distance {
+            closest_distance = distance;
+            closest = Some(other);
+        }
+    }
+    match closest {
+        Some(other) => {
+            let sight = context.genotype.sight_distance.max(1.);
+            (
+                Some(other),
+                (
+                    (other.position.0 - agent.position.0) / sight,
+                    (other.position.1 - agent.position.1) / sight,
+                ),
+            )
+        }
+        None => (None, (0., 0.)),
+    }
+}
+
+// What an agent can perceive, shared by every kind of brain
+pub struct Senses<'a> {
+    pub inputs: [f32; BRAIN_INPUTS],
+    food: Option<&'a Agent>,
+    threat: Option<&'a Agent>,
+    threat_direction: (f32, f32),
+    mate: Option<&'a Agent>,
+}
+
+pub fn sense<'a>(agent: &Agent, context: &Context<'a>) -> Senses<'a> {
+    let genotype = &context.genotype;
+    let (food, food_direction) = nearest(agent, context, |other| {
+        !other.dead && context.species.eats(&agent.kind, &other.kind)
+    });
+    let (threat, threat_direction) = nearest(agent, context, |other| {
+        !other.dead && context.species.fears(&agent.kind, &other.kind)
+    });
+    let (mate, mate_direction) = nearest(agent, context, |other| agent.can_mate_with(other));
+
+    Senses {
+        inputs: [
+            food_direction.0,
+            food_direction.1,
+            threat_direction.0,
+            threat_direction.1,
+            mate_direction.0,
+            mate_direction.1,
+            agent.hunger / 100.,
+            agent.health / genotype.health_scale.max(1.),
+            agent.stamina / genotype.stamina_capacity.max(1.),
+            context.clock.daylight(),
+        ],
+        food,
+        threat,
+        threat_direction,
+        mate,
+    }
+}
+
+// Turns the network outputs into steering, eating and mating
+pub fn respond(
+    agent: &mut Agent,
+    context: &Context,
+    senses: &Senses,
+    outputs: &[f32; BRAIN_OUTPUTS],
+    modified_agents: &mut HashMap<Uuid, Agent>,
+) {
+    let genotype = &context.genotype;
+    let top_speed = genotype.movement_speed * genotype.sprint_multiplier;
+    agent.acceleration.0 = outputs[0] * top_speed;
+    agent.acceleration.1 = outputs[1] * top_speed;
+    agent.state = State::Idle;
+
+    // Running away from a threat counts as fleeing, so the agent sprints
+    if senses.threat.is_some()
+        && outputs[0] * senses.threat_direction.0 + outputs[1] * senses.threat_direction.1 < 0.
+    {
+        agent.state = State::Fleeing;
+    }
+
+    if outputs[2] > 0. {
+        if let Some(food) = senses.food {
+            let mut food = food.clone();
+            if bite(agent, &mut food, context, modified_agents) {
+                agent.state = State::Hunting(food.id);
+                modified_agents.insert(food.id, food);
+            }
+        }
+    } else if outputs[3] > 0. && agent.pregnancy.is_none() {
+        if let Some(mate) = senses.mate {
+            if genotype.fertility(agent.life) > 0. {
+                let mut mate = mate.clone();
+                if court(agent, &mut mate, context) {
+                    modified_agents.insert(mate.id, mate);
+                }
+            }
+        }
+    }
+}
+
+// Lets the evolved network drive the agent instead of the hand written behaviours
+pub struct Think;
+impl Behaviour for Think {
+    fn name(&self) -> &'static str {
+        "Think"
+    }
+
+    fn is_running(&self, _agent: &Agent, _context: &Context) -> bool {
+        true
+    }
+
+    fn score(&self, _agent: &Agent, _context: &Context) -> f32 {
+        1.
+    }
+
+    fn act(
+        &self,
+        agent: &mut Agent,
+        context: &Context,
+        _thread_rng: &mut ThreadRng,
+        modified_agents: &mut HashMap<Uuid, Agent>,
+    ) {
+        let senses = sense(agent, context);
+        let outputs = think(&context.genotype.brain, &senses.inputs);
+        respond(agent, context, &senses, &outputs, modified_agents);
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use rand::rngs::ThreadRng;
+use uuid::Uuid;
+
+use crate::{
+    agent::{Agent, State},
+    behaviour::{distance_squared, head_towards, Behaviour, Context},
+};
+
+const FOLLOW_SCORE: f32 = 0.45;
+// Juveniles try to stay this close to their parent
+const FOLLOW_DISTANCE: f32 = 15.;
+// Parents pass food on to their young within this distance
+const FEEDING_DISTANCE: f32 = 10.;
+// Parents only share while at least this well fed, and only with young hungrier than this
+const FEEDING_HUNGER: f32 = 50.;
+const HUNGRY_YOUNG: f32 = 60.;
+// Food passed on per tick by a fully investing parent
+const FEEDING_RATE: f32 = 1.;
+
+pub fn is_juvenile(agent: &Agent, context: &Context) -> bool {
+    agent.life < context.genotype.maturity_age
+}
+
+// The parent if it's still alive and in sight
+fn parent<'a>(agent: &Agent, context: &Context<'a>) -> Option<&'a Agent> {
+    context
+        .get_nearby(agent.parent?)
+        .filter(|parent| !parent.dead && parent.kind.is_animal())
+}
+
+// Passes some food on to each hungry young of the agent within reach
+pub fn feed_young(
+    agent: &mut Agent,
+    context: &Context,
+    modified_agents: &mut HashMap<Uuid, Agent>,
+) {
+    let investment = context.genotype.parental_investment;
+    if investment <= 0. || is_juvenile(agent, context) {
+        return;
+    }
+    for young in context.neighbours(agent) {
+        if agent.hunger <= FEEDING_HUNGER {
+            return;
+        }
+        if young.dead
+            || !young.kind.is_animal()
+            || young.parent != Some(agent.id)
+            || young.hunger >= HUNGRY_YOUNG
+            || distance_squared(agent, young) > FEEDING_DISTANCE.powi(2)
+        {
+            continue;
+        }
+        let food = FEEDING_RATE * investment;
+        let mut young = young.clone();
+        agent.hunger -= food;
+        young.hunger = (young.hunger + food).min(100.);
+        modified_agents.insert(young.id, young);
+    }
+}
+
+// Juveniles keep close to the parent that raises them
+pub struct FollowParent;
+impl Behaviour for FollowParent {
+    fn name(&self) -> &'static str {
+        "FollowParent"
+    }
+
+    fn is_running(&self, agent: &Agent, _context: &Context) -> bool {
+        matches!(agent.state, State::Following)
+    }
+
+    fn score(&self, agent: &Agent, context: &Context) -> f32 {
+        if !is_juvenile(agent, context) {
+            return 0.;
+        }
+        match parent(agent, context) {
+            Some(parent) if distance_squared(agent, parent) > FOLLOW_DISTANCE.powi(2) => {
+                // Clinging fades as the juvenile grows up
+                FOLLOW_SCORE * (1. - agent.life / context.genotype.maturity_age.max(1.))
+            }
+            _ => 0.,
+        }
+    }
+
+    fn act(
+        &self,
+        agent: &mut Agent,
+        context: &Context,
+        _thread_rng: &mut ThreadRng,
+        _modified_agents: &mut HashMap<Uuid, Agent>,
+    ) {
+        match parent(agent, context) {
+            Some(parent) => {
+                agent.state = State::Following;
+                let target = (parent.position.0, parent.position.1);
+                head_towards(agent, context, target, context.genotype.movement_speed);
+            }
+            None => agent.state = State::Idle,
+        }
+    }
+}
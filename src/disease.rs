@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+
+use rand::{rngs::ThreadRng, Rng};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    agent::{Agent, DeathCause},
+    behaviour::{distance_squared, Context},
+    species::SpeciesId,
+};
+
+// Agents closer than this can pass on an infection
+const CONTACT_DISTANCE: f32 = 15.;
+// Fraction of the infectious period a fully immune host shaves off
+const IMMUNE_RECOVERY: f32 = 0.5;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Pathogen {
+    pub id: usize,
+    pub name: String,
+    // Species it can infect
+    pub hosts: Vec<SpeciesId>,
+    // Chance per tick to infect a susceptible host in contact
+    pub transmissibility: f32,
+    // Health lost per tick while infectious
+    pub virulence: f32,
+    // Time spent exposed before becoming infectious, 0 makes it a plain SIR disease
+    pub incubation: f32,
+    pub infectious_period: f32,
+    // How long recovered hosts stay immune, 0 means for life
+    pub immunity_duration: f32,
+}
+
+// A pathogen as described from JS, hosts are species names
+#[derive(Deserialize)]
+pub struct PathogenDefinition {
+    pub name: String,
+    pub hosts: Vec<String>,
+    pub transmissibility: f32,
+    pub virulence: f32,
+    #[serde(default)]
+    pub incubation: f32,
+    pub infectious_period: f32,
+    #[serde(default)]
+    pub immunity_duration: f32,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Stage {
+    Exposed,
+    Infectious,
+    Recovered,
+}
+impl Stage {
+    pub fn to_int(self) -> u8 {
+        match self {
+            Stage::Exposed => 1,
+            Stage::Infectious => 2,
+            Stage::Recovered => 3,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Infection {
+    pub pathogen: usize,
+    pub stage: Stage,
+    pub since: f32,
+}
+
+// Worst stage among an agent's infections, 0 susceptible, 1 exposed, 2 infectious, 3 recovered
+pub fn stage_to_int(infections: &[Infection]) -> u8 {
+    let has = |stage| infections.iter().any(|infection| infection.stage == stage);
+    if has(Stage::Infectious) {
+        Stage::Infectious.to_int()
+    } else if has(Stage::Exposed) {
+        Stage::Exposed.to_int()
+    } else if has(Stage::Recovered) {
+        Stage::Recovered.to_int()
+    } else {
+        0
+    }
+}
+
+// Whether the agent is carrying or immune to a pathogen
+pub fn has_pathogen(agent: &Agent, pathogen: usize) -> bool {
+    agent
+        .infections
+        .iter()
+        .any(|infection| infection.pathogen == pathogen)
+}
+
+fn new_infection(pathogen: &Pathogen, time: f32) -> Infection {
+    Infection {
+        pathogen: pathogen.id,
+        stage: if pathogen.incubation > 0. {
+            Stage::Exposed
+        } else {
+            Stage::Infectious
+        },
+        since: time,
+    }
+}
+
+// Moves each infection through its stages, then catches pathogens from infectious neighbours
+pub fn update_infection(agent: &mut Agent, context: &Context, thread_rng: &mut ThreadRng) {
+    let immunity = context.genotype.immunity.clamp(0., 1.);
+    let mut damage = 0.;
+    for infection in agent.infections.iter_mut() {
+        let pathogen = &context.pathogens[infection.pathogen];
+        let elapsed = context.time - infection.since;
+        match infection.stage {
+            Stage::Exposed => {
+                if elapsed >= pathogen.incubation {
+                    infection.stage = Stage::Infectious;
+                    infection.since = context.time;
+                }
+            }
+            Stage::Infectious => {
+                damage += pathogen.virulence * (1. - immunity);
+                if elapsed >= pathogen.infectious_period * (1. - immunity * IMMUNE_RECOVERY) {
+                    infection.stage = Stage::Recovered;
+                    infection.since = context.time;
+                }
+            }
+            Stage::Recovered => {}
+        }
+    }
+    // Waning immunity makes the host susceptible again
+    agent.infections.retain(|infection| {
+        let pathogen = &context.pathogens[infection.pathogen];
+        infection.stage != Stage::Recovered
+            || pathogen.immunity_duration <= 0.
+            || context.time - infection.since < pathogen.immunity_duration
+    });
+    if damage > 0. {
+        agent.health -= damage;
+        if agent.health <= 0. {
+            agent.die(DeathCause::Disease);
+            return;
+        }
+    }
+
+    let species = agent.kind.species();
+    let mut caught: Vec<Infection> = Vec::new();
+    for other in context.neighbours(agent) {
+        if other.dead || distance_squared(agent, other) > CONTACT_DISTANCE.powi(2) {
+            continue;
+        }
+        for infection in other.infections.iter() {
+            let pathogen = &context.pathogens[infection.pathogen];
+            if infection.stage != Stage::Infectious
+                || !pathogen.hosts.contains(&species)
+                || has_pathogen(agent, pathogen.id)
+                || caught.iter().any(|new| new.pathogen == pathogen.id)
+            {
+                continue;
+            }
+            if thread_rng.gen::<f32>() < pathogen.transmissibility * (1. - immunity) {
+                caught.push(new_infection(pathogen, context.time));
+            }
+        }
+    }
+    agent.infections.extend(caught);
+}
+
+// Patient zeroes, infects up to count random susceptible hosts
+pub fn seed_infection(
+    agents: &mut HashMap<Uuid, Agent>,
+    pathogen: &Pathogen,
+    count: usize,
+    time: f32,
+) {
+    let mut infected = 0;
+    for agent in agents.values_mut() {
+        if infected >= count {
+            break;
+        }
+        if agent.dead
+            || has_pathogen(agent, pathogen.id)
+            || !agent.kind.is_animal()
+            || !pathogen.hosts.contains(&agent.kind.species())
+        {
+            continue;
+        }
+        agent.infections.push(new_infection(pathogen, time));
+        infected += 1;
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SerializedEpidemic {
+    pub name: String,
+    pub susceptible: usize,
+    pub exposed: usize,
+    pub infectious: usize,
+    pub recovered: usize,
+}
+
+// Living hosts of each pathogen split by stage
+pub fn statistics(
+    agents: &HashMap<Uuid, Agent>,
+    pathogens: &[Pathogen],
+) -> Vec<SerializedEpidemic> {
+    pathogens
+        .iter()
+        .map(|pathogen| {
+            let mut result = SerializedEpidemic {
+                name: pathogen.name.clone(),
+                susceptible: 0,
+                exposed: 0,
+                infectious: 0,
+                recovered: 0,
+            };
+            for agent in agents.values() {
+                if agent.dead
+                    || !agent.kind.is_animal()
+                    || !pathogen.hosts.contains(&agent.kind.species())
+                {
+                    continue;
+                }
+                match agent
+                    .infections
+                    .iter()
+                    .find(|infection| infection.pathogen == pathogen.id)
+                {
+                    Some(infection) => match infection.stage {
+                        Stage::Exposed => result.exposed += 1,
+                        Stage::Infectious => result.infectious += 1,
+                        Stage::Recovered => result.recovered += 1,
+                    },
+                    None => result.susceptible += 1,
+                }
+            }
+            result
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{agent::AgentType, genes::Genotype};
+
+    fn pathogen(id: usize) -> Pathogen {
+        Pathogen {
+            id,
+            name: format!("pathogen {}", id),
+            hosts: vec![1],
+            transmissibility: 0.5,
+            virulence: 1.,
+            incubation: 0.,
+            infectious_period: 10.,
+            immunity_duration: 0.,
+        }
+    }
+
+    fn host(thread_rng: &mut ThreadRng) -> Agent {
+        let kind = AgentType::Animal(1, Genotype::new(thread_rng));
+        Agent::new(kind, (0., 0.), Uuid::new_v4(), 0., 0.)
+    }
+
+    #[test]
+    fn seeding_a_second_pathogen_keeps_the_first() {
+        let mut thread_rng = rand::thread_rng();
+        let pathogens = [pathogen(0), pathogen(1)];
+        let agent = host(&mut thread_rng);
+        let id = agent.id;
+        let mut agents = HashMap::new();
+        agents.insert(id, agent);
+
+        seed_infection(&mut agents, &pathogens[0], 1, 0.);
+        seed_infection(&mut agents, &pathogens[1], 1, 0.);
+        seed_infection(&mut agents, &pathogens[1], 1, 0.);
+
+        let infections = &agents[&id].infections;
+        assert_eq!(infections.len(), 2);
+        assert!(has_pathogen(&agents[&id], 0) && has_pathogen(&agents[&id], 1));
+        assert_eq!(stage_to_int(infections), Stage::Infectious.to_int());
+    }
+
+    #[test]
+    fn statistics_count_each_pathogen_separately() {
+        let mut thread_rng = rand::thread_rng();
+        let pathogens = [pathogen(0), pathogen(1)];
+        let mut sick = host(&mut thread_rng);
+        sick.infections = vec![
+            Infection {
+                pathogen: 0,
+                stage: Stage::Infectious,
+                since: 0.,
+            },
+            Infection {
+                pathogen: 1,
+                stage: Stage::Recovered,
+                since: 0.,
+            },
+        ];
+        let healthy = host(&mut thread_rng);
+        let mut agents = HashMap::new();
+        agents.insert(sick.id, sick);
+        agents.insert(healthy.id, healthy);
+
+        let epidemics = statistics(&agents, &pathogens);
+        assert_eq!(
+            (
+                epidemics[0].susceptible,
+                epidemics[0].infectious,
+                epidemics[0].recovered
+            ),
+            (1, 1, 0)
+        );
+        assert_eq!(
+            (
+                epidemics[1].susceptible,
+                epidemics[1].infectious,
+                epidemics[1].recovered
+            ),
+            (1, 0, 1)
+        );
+    }
+}
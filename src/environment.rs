@@ -0,0 +1,156 @@
+use std::{f32::consts::PI, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+
+// Durations are expressed in simulation seconds
+const DEFAULT_DAY_LENGTH: f32 = 60.;
+const DEFAULT_DAYS_PER_YEAR: f32 = 8.;
+
+// How much sight shrinks in the middle of the night
+const NIGHT_SIGHT_FACTOR: f32 = 0.4;
+// How much agents slow down their wandering at night
+const NIGHT_ACTIVITY_FACTOR: f32 = 0.5;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+impl Season {
+    pub fn to_int(self) -> u8 {
+        match self {
+            Season::Spring => 0,
+            Season::Summer => 1,
+            Season::Autumn => 2,
+            Season::Winter => 3,
+        }
+    }
+
+    pub fn growth_multiplier(&self) -> f32 {
+        match self {
+            Season::Spring => 1.5,
+            Season::Summer => 1.,
+            Season::Autumn => 0.5,
+            Season::Winter => 0.1,
+        }
+    }
+
+    pub fn hunger_multiplier(&self) -> f32 {
+        match self {
+            Season::Spring => 1.,
+            Season::Summer => 0.9,
+            Season::Autumn => 1.,
+            Season::Winter => 1.4,
+        }
+    }
+}
+
+impl Display for Season {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Season::Spring => write!(f, "Spring"),
+            Season::Summer => write!(f, "Summer"),
+            Season::Autumn => write!(f, "Autumn"),
+            Season::Winter => write!(f, "Winter"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Clock {
+    pub time: f32,
+    pub day_length: f32,
+    pub days_per_year: f32,
+    pub cycles_enabled: bool,
+}
+
+impl Clock {
+    pub fn new() -> Clock {
+        Clock {
+            time: 0.,
+            day_length: DEFAULT_DAY_LENGTH,
+            days_per_year: DEFAULT_DAYS_PER_YEAR,
+            cycles_enabled: true,
+        }
+    }
+
+    pub fn update(&mut self, time: f32) {
+        self.time = time;
+    }
+
+    // 0 is midnight, 0.5 is noon
+    pub fn time_of_day(&self) -> f32 {
+        (self.time / self.day_length).fract()
+    }
+
+    // 0 at the start of spring, wraps at the end of winter
+    pub fn year_progress(&self) -> f32 {
+        (self.time / (self.day_length * self.days_per_year)).fract()
+    }
+
+    // Amount of light between 0 (night) and 1 (noon)
+    pub fn daylight(&self) -> f32 {
+        if !self.cycles_enabled {
+            return 1.;
+        }
+        (0.5 - 0.5 * (self.time_of_day() * PI * 2.).cos()).clamp(0., 1.)
+    }
+
+    pub fn is_night(&self) -> bool {
+        self.daylight() < 0.25
+    }
+
+    pub fn season(&self) -> Season {
+        match (self.year_progress() * 4.) as u8 {
+            0 => Season::Spring,
+            1 => Season::Summer,
+            2 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+
+    pub fn sight_multiplier(&self) -> f32 {
+        NIGHT_SIGHT_FACTOR + (1. - NIGHT_SIGHT_FACTOR) * self.daylight()
+    }
+
+    pub fn activity_multiplier(&self) -> f32 {
+        NIGHT_ACTIVITY_FACTOR + (1. - NIGHT_ACTIVITY_FACTOR) * self.daylight()
+    }
+
+    pub fn growth_multiplier(&self) -> f32 {
+        if !self.cycles_enabled {
+            return 1.;
+        }
+        self.season().growth_multiplier()
+    }
+
+    pub fn hunger_multiplier(&self) -> f32 {
+        if !self.cycles_enabled {
+            return 1.;
+        }
+        self.season().hunger_multiplier()
+    }
+
+    pub fn serialize(&self) -> SerializedClock {
+        SerializedClock {
+            time: self.time,
+            time_of_day: self.time_of_day(),
+            daylight: self.daylight(),
+            year_progress: self.year_progress(),
+            season: self.season().to_int(),
+            season_name: self.season().to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SerializedClock {
+    pub time: f32,
+    pub time_of_day: f32,
+    pub daylight: f32,
+    pub year_progress: f32,
+    pub season: u8,
+    pub season_name: String,
+}
@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    agent::Agent,
+    behaviour::{normalize, Context},
+    species::Role,
+    vector_length, Bounds,
+};
+
+const FIELD_CELL_SIZE: f32 = 32.;
+// Share of a cell's value that spreads to its neighbours every tick
+const SCENT_DIFFUSION: f32 = 0.1;
+const FEAR_DIFFUSION: f32 = 0.02;
+// Share of the value lost every tick, fear of a kill site lingers much longer than a trail
+const SCENT_EVAPORATION: f32 = 0.02;
+const FEAR_EVAPORATION: f32 = 0.001;
+// Amount left behind by an animal every tick and by a kill
+pub const SCENT_DEPOSIT: f32 = 1.;
+pub const FEAR_DEPOSIT: f32 = 50.;
+// Gradients weaker than this are too faint to follow
+const MIN_GRADIENT: f32 = 0.05;
+
+// Scalar value on a coarse grid over the world that spreads out and fades over time
+#[derive(Clone)]
+pub struct Field {
+    bounds: Bounds,
+    columns: usize,
+    rows: usize,
+    diffusion: f32,
+    evaporation: f32,
+    pub values: Vec<f32>,
+}
+
+impl Field {
+    pub fn new(bounds: Bounds, diffusion: f32, evaporation: f32) -> Field {
+        let columns = (bounds.width / FIELD_CELL_SIZE).ceil().max(1.) as usize;
+        let rows = (bounds.height / FIELD_CELL_SIZE).ceil().max(1.) as usize;
+        Field {
+            bounds,
+            columns,
+            rows,
+            diffusion,
+            evaporation,
+            values: vec![0.; columns * rows],
+        }
+    }
+
+    fn index_at(&self, point: (f32, f32)) -> usize {
+        let column = ((point.0 - self.bounds.x) / FIELD_CELL_SIZE)
+            .floor()
+            .max(0.) as usize;
+        let row = ((point.1 - self.bounds.y) / FIELD_CELL_SIZE)
+            .floor()
+            .max(0.) as usize;
+        row.min(self.rows - 1) * self.columns + column.min(self.columns - 1)
+    }
+
+    pub fn sample(&self, point: (f32, f32)) -> f32 {
+        self.values[self.index_at(point)]
+    }
+
+    pub fn deposit(&mut self, point: (f32, f32), amount: f32) {
+        let index = self.index_at(point);
+        self.values[index] += amount;
+    }
+
+    // Direction in which the value increases, from the cells on either side of the point
+    pub fn gradient(&self, point: (f32, f32)) -> (f32, f32) {
+        (
+            (self.sample((point.0 + FIELD_CELL_SIZE, point.1))
+                - self.sample((point.0 - FIELD_CELL_SIZE, point.1)))
+                / 2.,
+            (self.sample((point.0, point.1 + FIELD_CELL_SIZE))
+                - self.sample((point.0, point.1 - FIELD_CELL_SIZE)))
+                / 2.,
+        )
+    }
+
+    // Spreads every cell towards the average of its neighbours and evaporates a share of it,
+    // cells on the edge use their own value for the missing neighbours
+    pub fn update(&mut self) {
+        let mut next = vec![0.; self.values.len()];
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let index = row * self.columns + column;
+                let value = self.values[index];
+                let left = if column > 0 {
+                    self.values[index - 1]
+                } else {
+                    value
+                };
+                let right = if column + 1 < self.columns {
+                    self.values[index + 1]
+                } else {
+                    value
+                };
+                let up = if row > 0 {
+                    self.values[index - self.columns]
+                } else {
+                    value
+                };
+                let down = if row + 1 < self.rows {
+                    self.values[index + self.columns]
+                } else {
+                    value
+                };
+                let average = (left + right + up + down) / 4.;
+                next[index] =
+                    (value + (average - value) * self.diffusion) * (1. - self.evaporation);
+            }
+        }
+        self.values = next;
+    }
+
+    pub fn serialize(&self) -> SerializedField {
+        SerializedField {
+            columns: self.columns,
+            rows: self.rows,
+            cell_size: FIELD_CELL_SIZE,
+            values: self.values.clone(),
+        }
+    }
+}
+
+// Scent trails left by every species and fear markers where members of it were killed
+pub struct Fields {
+    bounds: Bounds,
+    pub scent: Vec<Field>,
+    pub fear: Vec<Field>,
+}
+
+impl Fields {
+    pub fn new(bounds: Bounds, species_count: usize) -> Fields {
+        let mut fields = Fields {
+            bounds,
+            scent: Vec::new(),
+            fear: Vec::new(),
+        };
+        fields.resize(species_count);
+        fields
+    }
+
+    // Adds empty fields for newly registered species
+    pub fn resize(&mut self, species_count: usize) {
+        while self.scent.len() < species_count {
+            self.scent
+                .push(Field::new(self.bounds, SCENT_DIFFUSION, SCENT_EVAPORATION));
+            self.fear
+                .push(Field::new(self.bounds, FEAR_DIFFUSION, FEAR_EVAPORATION));
+        }
+    }
+
+    pub fn update(&mut self) {
+        for field in self.scent.iter_mut().chain(self.fear.iter_mut()) {
+            field.update();
+        }
+    }
+}
+
+// Where the fields lead a wandering animal, up the scent of its prey and away from places
+// its own kind was killed
+pub fn wander_direction(agent: &Agent, context: &Context) -> Option<(f32, f32)> {
+    let position = (agent.position.0, agent.position.1);
+    let species = agent.kind.species();
+    let mut pull = (0., 0.);
+    for food in context.species.get(species).diet.iter() {
+        if context.species.get(food.species).role != Role::Animal {
+            continue;
+        }
+        let gradient = context.fields.scent[food.species].gradient(position);
+        pull.0 += gradient.0 * food.preference;
+        pull.1 += gradient.1 * food.preference;
+    }
+    let fear = context.fields.fear[species].gradient(position);
+    pull.0 -= fear.0;
+    pull.1 -= fear.1;
+
+    if vector_length(pull) < MIN_GRADIENT {
+        return None;
+    }
+    Some(normalize(pull))
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SerializedField {
+    pub columns: usize,
+    pub rows: usize,
+    pub cell_size: f32,
+    // Row major, starting at the world origin
+    pub values: Vec<f32>,
+}
@@ -1,58 +1,253 @@
 use rand::{rngs::ThreadRng, Rng};
+
 use std::{collections::HashMap};
 
-#[derive(Clone, Copy, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+use crate::brain::{self, BrainWeights};
+
+#[derive(Clone, PartialEq)]
 pub struct Genotype {
     // Genotype Variables
     pub body_size: f32,
     pub sight_distance: f32,
     pub muscle_mass: f32,
     pub reproduction_chance: f32,
+    pub litter_size: f32,
+    pub max_lifespan: f32,
+    pub display: f32,
+    pub mate_preference: f32,
+    pub choosiness: f32,
+    pub endurance: f32,
+    // Behaviour weights
+    pub curiosity: f32,
+    pub laziness: f32,
+    pub fear: f32,
+    pub appetite: f32,
+    pub libido: f32,
+    // Flocking weights
+    pub separation: f32,
+    pub alignment: f32,
+    pub cohesion: f32,
+    // Pack hunting
+    pub sociability: f32,
+    // Diet specialisation
+    pub carnivory: f32,
+    pub selectivity: f32,
+    // Plant defences
+    pub toxin_tolerance: f32,
+    // Disease resistance
+    pub immunity: f32,
+    // Spatial memory
+    pub memory_capacity: f32,
+    // Radius of the territory defended around the den
+    pub territory_size: f32,
+    // Share of its food a parent is willing to pass on to its young
+    pub parental_investment: f32,
+    // Chance of warning others of its kind on spotting a predator
+    pub alarm_propensity: f32,
+    // Weights of the optional neural network brain
+    pub brain: BrainWeights,
     // Derived Variables
     pub hunger_rate: f32,
+    pub mass: f32,
     pub health_scale: f32,
     pub movement_speed: f32,
     pub gestation_duration: f32,
+    pub maturity_age: f32,
+    pub stamina_capacity: f32,
+    pub stamina_recovery: f32,
+    pub sprint_multiplier: f32,
 }
 
 const BODY_SIZE_RANGE: std::ops::Range<f32> = 5.0..10.0;
 const SIGHT_DISTANCE_RANGE: std::ops::Range<f32> = 50.0..200.;
 const MUSCLE_MASS_RANGE: std::ops::Range<f32> = 1.0..10.0;
 const REPRODUCTION_CHANCE_RANGE: std::ops::Range<f32> = 0.0..1.0;
+const LITTER_SIZE_RANGE: std::ops::Range<f32> = 1.0..4.0;
+const MAX_LIFESPAN_RANGE: std::ops::Range<f32> = 300.0..900.0;
+const DISPLAY_RANGE: std::ops::Range<f32> = 0.0..1.0;
+const MATE_PREFERENCE_RANGE: std::ops::Range<f32> = 0.0..1.0;
+const CHOOSINESS_RANGE: std::ops::Range<f32> = 0.0..0.5;
+const ENDURANCE_RANGE: std::ops::Range<f32> = 0.0..1.0;
+const BEHAVIOUR_WEIGHT_RANGE: std::ops::Range<f32> = 0.5..1.5;
+const FLOCKING_WEIGHT_RANGE: std::ops::Range<f32> = 0.0..1.0;
+const SOCIABILITY_RANGE: std::ops::Range<f32> = 0.0..1.0;
+const DIET_GENE_RANGE: std::ops::Range<f32> = 0.0..1.0;
+const TOXIN_TOLERANCE_RANGE: std::ops::Range<f32> = 0.0..0.5;
+const IMMUNITY_RANGE: std::ops::Range<f32> = 0.0..0.5;
+const MEMORY_CAPACITY_RANGE: std::ops::Range<f32> = 0.0..8.0;
+const TERRITORY_SIZE_RANGE: std::ops::Range<f32> = 40.0..160.0;
+const PARENTAL_INVESTMENT_RANGE: std::ops::Range<f32> = 0.0..1.0;
+const ALARM_PROPENSITY_RANGE: std::ops::Range<f32> = 0.0..1.0;
+
+// Per species (min, max) ranges for the genes that set an animal's build
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeneSchema {
+    pub body_size: (f32, f32),
+    pub sight_distance: (f32, f32),
+    pub muscle_mass: (f32, f32),
+    pub litter_size: (f32, f32),
+    pub max_lifespan: (f32, f32),
+}
+
+impl Default for GeneSchema {
+    fn default() -> GeneSchema {
+        GeneSchema {
+            body_size: (BODY_SIZE_RANGE.start, BODY_SIZE_RANGE.end),
+            sight_distance: (SIGHT_DISTANCE_RANGE.start, SIGHT_DISTANCE_RANGE.end),
+            muscle_mass: (MUSCLE_MASS_RANGE.start, MUSCLE_MASS_RANGE.end),
+            litter_size: (LITTER_SIZE_RANGE.start, LITTER_SIZE_RANGE.end),
+            max_lifespan: (MAX_LIFESPAN_RANGE.start, MAX_LIFESPAN_RANGE.end),
+        }
+    }
+}
+
+fn gen_between(range: (f32, f32), thread_rng: &mut ThreadRng) -> f32 {
+    range.0 + thread_rng.gen::<f32>() * (range.1 - range.0)
+}
 
+// Extra hunger caused by carrying a full display
+const DISPLAY_COST: f32 = 0.5;
+// Extra hunger caused by keeping up a full toxin tolerance or immune system
+const TOLERANCE_COST: f32 = 0.3;
+const IMMUNITY_COST: f32 = 0.3;
+// Extra hunger for every remembered location
+const MEMORY_COST: f32 = 0.02;
+
+// Fraction of the lifespan spent as a juvenile
+const MATURITY_FRACTION: f32 = 0.15;
+// Size of a newborn relative to its adult body size
+pub const JUVENILE_SIZE: f32 = 0.3;
+// Fraction of the lifespan after which senescence kicks in
+const SENESCENCE_FRACTION: f32 = 0.6;
+
+// Largest mutation step as a share of the gene's range
 const MUTATION_RATE: f32 = 0.05;
 
+// Nudges a gene by a step scaled to its range, keeping it inside the range
+fn mutate_gene(value: f32, range: std::ops::Range<f32>, thread_rng: &mut ThreadRng) -> f32 {
+    let (low, high) = (range.start.min(range.end), range.start.max(range.end));
+    let step = (thread_rng.gen::<f32>() * 2. - 1.) * MUTATION_RATE * (high - low);
+    (value + step).clamp(low, high)
+}
+
 impl Genotype {
     pub fn new(thread_rng: &mut ThreadRng) -> Genotype {
         let body_size = thread_rng.gen_range(BODY_SIZE_RANGE);
         let sight_distance = thread_rng.gen_range(SIGHT_DISTANCE_RANGE);
         let muscle_mass = thread_rng.gen_range(MUSCLE_MASS_RANGE);
         let reproduction_chance = thread_rng.gen_range(REPRODUCTION_CHANCE_RANGE);
+        let litter_size = thread_rng.gen_range(LITTER_SIZE_RANGE);
+        let max_lifespan = thread_rng.gen_range(MAX_LIFESPAN_RANGE);
+        let display = thread_rng.gen_range(DISPLAY_RANGE);
+        let mate_preference = thread_rng.gen_range(MATE_PREFERENCE_RANGE);
+        let choosiness = thread_rng.gen_range(CHOOSINESS_RANGE);
+        let endurance = thread_rng.gen_range(ENDURANCE_RANGE);
+        let curiosity = thread_rng.gen_range(BEHAVIOUR_WEIGHT_RANGE);
+        let laziness = thread_rng.gen_range(BEHAVIOUR_WEIGHT_RANGE);
+        let fear = thread_rng.gen_range(BEHAVIOUR_WEIGHT_RANGE);
+        let appetite = thread_rng.gen_range(BEHAVIOUR_WEIGHT_RANGE);
+        let libido = thread_rng.gen_range(BEHAVIOUR_WEIGHT_RANGE);
+        let separation = thread_rng.gen_range(FLOCKING_WEIGHT_RANGE);
+        let alignment = thread_rng.gen_range(FLOCKING_WEIGHT_RANGE);
+        let cohesion = thread_rng.gen_range(FLOCKING_WEIGHT_RANGE);
+        let sociability = thread_rng.gen_range(SOCIABILITY_RANGE);
+        let carnivory = thread_rng.gen_range(DIET_GENE_RANGE);
+        let selectivity = thread_rng.gen_range(DIET_GENE_RANGE);
+        let toxin_tolerance = thread_rng.gen_range(TOXIN_TOLERANCE_RANGE);
+        let immunity = thread_rng.gen_range(IMMUNITY_RANGE);
+        let memory_capacity = thread_rng.gen_range(MEMORY_CAPACITY_RANGE);
+        let territory_size = thread_rng.gen_range(TERRITORY_SIZE_RANGE);
+        let parental_investment = thread_rng.gen_range(PARENTAL_INVESTMENT_RANGE);
+        let alarm_propensity = thread_rng.gen_range(ALARM_PROPENSITY_RANGE);
+        let brain = brain::random_weights(thread_rng);
 
         let mut genotype = Genotype {
             body_size,
             sight_distance,
             muscle_mass,
             reproduction_chance,
+            litter_size,
+            max_lifespan,
+            display,
+            mate_preference,
+            choosiness,
+            endurance,
+            curiosity,
+            laziness,
+            fear,
+            appetite,
+            libido,
+            separation,
+            alignment,
+            cohesion,
+            sociability,
+            carnivory,
+            selectivity,
+            toxin_tolerance,
+            immunity,
+            memory_capacity,
+            territory_size,
+            parental_investment,
+            alarm_propensity,
+            brain,
             // Derived variables
             hunger_rate: 0.,
+            mass: 0.,
             health_scale: 0.,
             movement_speed: 0.,
             gestation_duration: 0.,
+            maturity_age: 0.,
+            stamina_capacity: 0.,
+            stamina_recovery: 0.,
+            sprint_multiplier: 0.,
         };
         genotype.derive_genotype();
 
         genotype
     }
 
+    // Random genotype whose build follows the species schema
+    pub fn from_schema(schema: &GeneSchema, thread_rng: &mut ThreadRng) -> Genotype {
+        let mut genotype = Genotype::new(thread_rng);
+        genotype.body_size = gen_between(schema.body_size, thread_rng);
+        genotype.sight_distance = gen_between(schema.sight_distance, thread_rng);
+        genotype.muscle_mass = gen_between(schema.muscle_mass, thread_rng);
+        genotype.litter_size = gen_between(schema.litter_size, thread_rng);
+        genotype.max_lifespan = gen_between(schema.max_lifespan, thread_rng);
+        genotype.derive_genotype();
+
+        genotype
+    }
+
     pub fn derive_genotype(&mut self) {
-        self.hunger_rate = self.body_size * self.muscle_mass;
+        // Basal metabolism grows with body size, displays, detoxification, immunity and memory
+        // are costly to maintain
+        self.hunger_rate = self.body_size
+            * (1.
+                + self.display * DISPLAY_COST
+                + self.toxin_tolerance * TOLERANCE_COST
+                + self.immunity * IMMUNITY_COST
+                + self.memory_slots() as f32 * MEMORY_COST);
+        self.mass = self.body_size + self.muscle_mass;
         self.health_scale = self.body_size * self.muscle_mass;
         self.movement_speed = self.muscle_mass / self.body_size;
         self.gestation_duration = self.body_size * self.muscle_mass;
+        self.maturity_age = self.max_lifespan * MATURITY_FRACTION;
+        // Endurance trades top sprinting speed for a larger and faster refilling stamina pool
+        self.stamina_capacity = self.muscle_mass * 10. * (0.5 + self.endurance);
+        self.stamina_recovery = 0.05 + 0.1 * self.endurance;
+        self.sprint_multiplier = 1. + (1. - self.endurance);
     }
 
-    pub fn crossbreed(&mut self, other: &Genotype, thread_rng: &mut ThreadRng) -> Genotype {
+    pub fn crossbreed(
+        &self,
+        other: &Genotype,
+        schema: &GeneSchema,
+        thread_rng: &mut ThreadRng,
+    ) -> Genotype {
         let body_size = if thread_rng.gen_bool(0.5) {
             self.body_size
         } else {
@@ -73,29 +268,213 @@ impl Genotype {
         } else {
             other.reproduction_chance
         };
+        let litter_size = if thread_rng.gen_bool(0.5) {
+            self.litter_size
+        } else {
+            other.litter_size
+        };
+        let max_lifespan = if thread_rng.gen_bool(0.5) {
+            self.max_lifespan
+        } else {
+            other.max_lifespan
+        };
+        let display = if thread_rng.gen_bool(0.5) {
+            self.display
+        } else {
+            other.display
+        };
+        let mate_preference = if thread_rng.gen_bool(0.5) {
+            self.mate_preference
+        } else {
+            other.mate_preference
+        };
+        let choosiness = if thread_rng.gen_bool(0.5) {
+            self.choosiness
+        } else {
+            other.choosiness
+        };
+        let endurance = if thread_rng.gen_bool(0.5) {
+            self.endurance
+        } else {
+            other.endurance
+        };
+        let curiosity = if thread_rng.gen_bool(0.5) {
+            self.curiosity
+        } else {
+            other.curiosity
+        };
+        let laziness = if thread_rng.gen_bool(0.5) {
+            self.laziness
+        } else {
+            other.laziness
+        };
+        let fear = if thread_rng.gen_bool(0.5) {
+            self.fear
+        } else {
+            other.fear
+        };
+        let appetite = if thread_rng.gen_bool(0.5) {
+            self.appetite
+        } else {
+            other.appetite
+        };
+        let libido = if thread_rng.gen_bool(0.5) {
+            self.libido
+        } else {
+            other.libido
+        };
+        let separation = if thread_rng.gen_bool(0.5) {
+            self.separation
+        } else {
+            other.separation
+        };
+        let alignment = if thread_rng.gen_bool(0.5) {
+            self.alignment
+        } else {
+            other.alignment
+        };
+        let cohesion = if thread_rng.gen_bool(0.5) {
+            self.cohesion
+        } else {
+            other.cohesion
+        };
+        let sociability = if thread_rng.gen_bool(0.5) {
+            self.sociability
+        } else {
+            other.sociability
+        };
+        let carnivory = if thread_rng.gen_bool(0.5) {
+            self.carnivory
+        } else {
+            other.carnivory
+        };
+        let selectivity = if thread_rng.gen_bool(0.5) {
+            self.selectivity
+        } else {
+            other.selectivity
+        };
+        let toxin_tolerance = if thread_rng.gen_bool(0.5) {
+            self.toxin_tolerance
+        } else {
+            other.toxin_tolerance
+        };
+        let immunity = if thread_rng.gen_bool(0.5) {
+            self.immunity
+        } else {
+            other.immunity
+        };
+        let memory_capacity = if thread_rng.gen_bool(0.5) {
+            self.memory_capacity
+        } else {
+            other.memory_capacity
+        };
+        let territory_size = if thread_rng.gen_bool(0.5) {
+            self.territory_size
+        } else {
+            other.territory_size
+        };
+        let parental_investment = if thread_rng.gen_bool(0.5) {
+            self.parental_investment
+        } else {
+            other.parental_investment
+        };
+        let alarm_propensity = if thread_rng.gen_bool(0.5) {
+            self.alarm_propensity
+        } else {
+            other.alarm_propensity
+        };
+        let brain = brain::crossover(&self.brain, &other.brain, thread_rng);
 
         let mut new_genotype = Genotype {
             body_size,
             sight_distance,
             muscle_mass,
             reproduction_chance,
+            litter_size,
+            max_lifespan,
+            display,
+            mate_preference,
+            choosiness,
+            endurance,
+            curiosity,
+            laziness,
+            fear,
+            appetite,
+            libido,
+            separation,
+            alignment,
+            cohesion,
+            sociability,
+            carnivory,
+            selectivity,
+            toxin_tolerance,
+            immunity,
+            memory_capacity,
+            territory_size,
+            parental_investment,
+            alarm_propensity,
+            brain,
             // Derived variables
             hunger_rate: 0.,
+            mass: 0.,
             health_scale: 0.,
             movement_speed: 0.,
             gestation_duration: 0.,
+            maturity_age: 0.,
+            stamina_capacity: 0.,
+            stamina_recovery: 0.,
+            sprint_multiplier: 0.,
         };
         new_genotype.derive_genotype();
-        new_genotype.mutate(thread_rng);
+        new_genotype.mutate(schema, thread_rng);
 
         new_genotype
     }
 
-    pub fn mutate(&mut self, thread_rng: &mut ThreadRng) {
-        self.body_size += (thread_rng.gen::<f32>() * 2. - 1.) * MUTATION_RATE;
-        self.sight_distance += (thread_rng.gen::<f32>() * 2. - 1.) * MUTATION_RATE;
-        self.muscle_mass += (thread_rng.gen::<f32>() * 2. - 1.) * MUTATION_RATE;
-        self.reproduction_chance += (thread_rng.gen::<f32>() * 2. - 1.) * MUTATION_RATE;
+    pub fn mutate(&mut self, schema: &GeneSchema, thread_rng: &mut ThreadRng) {
+        let between = |range: (f32, f32)| range.0..range.1;
+        self.body_size = mutate_gene(self.body_size, between(schema.body_size), thread_rng);
+        self.sight_distance = mutate_gene(
+            self.sight_distance,
+            between(schema.sight_distance),
+            thread_rng,
+        );
+        self.muscle_mass = mutate_gene(self.muscle_mass, between(schema.muscle_mass), thread_rng);
+        self.reproduction_chance = mutate_gene(
+            self.reproduction_chance,
+            REPRODUCTION_CHANCE_RANGE,
+            thread_rng,
+        );
+        self.litter_size = mutate_gene(self.litter_size, between(schema.litter_size), thread_rng);
+        self.max_lifespan =
+            mutate_gene(self.max_lifespan, between(schema.max_lifespan), thread_rng);
+        self.display = mutate_gene(self.display, DISPLAY_RANGE, thread_rng);
+        self.mate_preference = mutate_gene(self.mate_preference, MATE_PREFERENCE_RANGE, thread_rng);
+        self.choosiness = mutate_gene(self.choosiness, CHOOSINESS_RANGE, thread_rng);
+        self.endurance = mutate_gene(self.endurance, ENDURANCE_RANGE, thread_rng);
+        self.curiosity = mutate_gene(self.curiosity, BEHAVIOUR_WEIGHT_RANGE, thread_rng);
+        self.laziness = mutate_gene(self.laziness, BEHAVIOUR_WEIGHT_RANGE, thread_rng);
+        self.fear = mutate_gene(self.fear, BEHAVIOUR_WEIGHT_RANGE, thread_rng);
+        self.appetite = mutate_gene(self.appetite, BEHAVIOUR_WEIGHT_RANGE, thread_rng);
+        self.libido = mutate_gene(self.libido, BEHAVIOUR_WEIGHT_RANGE, thread_rng);
+        self.separation = mutate_gene(self.separation, FLOCKING_WEIGHT_RANGE, thread_rng);
+        self.alignment = mutate_gene(self.alignment, FLOCKING_WEIGHT_RANGE, thread_rng);
+        self.cohesion = mutate_gene(self.cohesion, FLOCKING_WEIGHT_RANGE, thread_rng);
+        self.sociability = mutate_gene(self.sociability, SOCIABILITY_RANGE, thread_rng);
+        self.carnivory = mutate_gene(self.carnivory, DIET_GENE_RANGE, thread_rng);
+        self.selectivity = mutate_gene(self.selectivity, DIET_GENE_RANGE, thread_rng);
+        self.toxin_tolerance = mutate_gene(self.toxin_tolerance, TOXIN_TOLERANCE_RANGE, thread_rng);
+        self.immunity = mutate_gene(self.immunity, IMMUNITY_RANGE, thread_rng);
+        self.memory_capacity = mutate_gene(self.memory_capacity, MEMORY_CAPACITY_RANGE, thread_rng);
+        self.territory_size = mutate_gene(self.territory_size, TERRITORY_SIZE_RANGE, thread_rng);
+        self.parental_investment = mutate_gene(
+            self.parental_investment,
+            PARENTAL_INVESTMENT_RANGE,
+            thread_rng,
+        );
+        self.alarm_propensity =
+            mutate_gene(self.alarm_propensity, ALARM_PROPENSITY_RANGE, thread_rng);
+        brain::mutate(&mut self.brain, thread_rng);
 
         self.derive_genotype();
     }
@@ -128,20 +507,210 @@ impl Genotype {
             self.movement_speed,
             self.gestation_duration,
             self.reproduction_chance,
+            self.litter_size,
+            self.max_lifespan,
+            self.display,
+            self.mate_preference,
+            self.choosiness,
+            self.endurance,
+            self.curiosity,
+            self.laziness,
+            self.fear,
+            self.appetite,
+            self.libido,
+            self.separation,
+            self.alignment,
+            self.cohesion,
+            self.sociability,
+            self.carnivory,
+            self.selectivity,
+            self.toxin_tolerance,
+            self.immunity,
+            self.memory_capacity,
+            self.territory_size,
+            self.parental_investment,
+            self.alarm_propensity,
         ]
     }
 
+    // Number of locations the agent can keep in mind at once
+    pub fn memory_slots(&self) -> usize {
+        self.memory_capacity.round().max(0.) as usize
+    }
+
+    // Number of offspring born from a single pregnancy
+    pub fn litter_count(&self) -> usize {
+        self.litter_size.round().max(1.) as usize
+    }
+
+    // Speed multiplier depending on age, the elderly are slower, juveniles are already held back
+    // by their size in at_age
+    pub fn vigor(&self, age: f32) -> f32 {
+        1. - 0.5 * self.senescence(age)
+    }
+
+    // Fraction of the adult body size reached, juveniles grow up until maturity
+    pub fn growth(&self, age: f32) -> f32 {
+        if age < self.maturity_age {
+            JUVENILE_SIZE + (1. - JUVENILE_SIZE) * (age / self.maturity_age).max(0.)
+        } else {
+            1.
+        }
+    }
+
+    // The body an animal of that age actually has, juveniles are smaller, slower, shorter sighted
+    // and lighter eaters than the adult their genes describe
+    pub fn at_age(&self, age: f32) -> Genotype {
+        let growth = self.growth(age);
+        let mut body = self.clone();
+        body.body_size *= growth;
+        body.sight_distance *= growth;
+        body.mass *= growth;
+        body.hunger_rate *= growth;
+        body.movement_speed *= growth;
+        body
+    }
+
+    // Chance multiplier for reproduction, null for juveniles and fading with old age
+    pub fn fertility(&self, age: f32) -> f32 {
+        if age < self.maturity_age {
+            0.
+        } else {
+            1. - 0.8 * self.senescence(age)
+        }
+    }
+
+    // How attractive a candidate mate looks, weighing its display against how big it is for
+    // its species
+    pub fn attractiveness(&self, candidate: &Genotype, schema: &GeneSchema) -> f32 {
+        let (low, high) = (
+            schema.body_size.0.min(schema.body_size.1),
+            schema.body_size.0.max(schema.body_size.1),
+        );
+        let size = ((candidate.body_size - low) / (high - low).max(f32::EPSILON)).clamp(0., 1.);
+        self.mate_preference * candidate.display + (1. - self.mate_preference) * size
+    }
+
+    pub fn accepts(&self, candidate: &Genotype, schema: &GeneSchema) -> bool {
+        self.attractiveness(candidate, schema) >= self.choosiness
+    }
+
+    // 0 until senescence starts, 1 at the end of the lifespan
+    fn senescence(&self, age: f32) -> f32 {
+        let onset = self.max_lifespan * SENESCENCE_FRACTION;
+        ((age - onset) / (self.max_lifespan - onset)).clamp(0., 1.)
+    }
+
     pub fn to_hashmap(&self) -> HashMap<String, f32> {
         let mut map = HashMap::new();
         map.insert("body_size".to_string(), self.body_size);
         map.insert("sight_distance".to_string(), self.sight_distance);
         map.insert("muscle_mass".to_string(), self.muscle_mass);
+        map.insert("litter_size".to_string(), self.litter_size);
+        map.insert("max_lifespan".to_string(), self.max_lifespan);
+        map.insert("display".to_string(), self.display);
+        map.insert("mate_preference".to_string(), self.mate_preference);
+        map.insert("choosiness".to_string(), self.choosiness);
+        map.insert("endurance".to_string(), self.endurance);
+        map.insert("curiosity".to_string(), self.curiosity);
+        map.insert("laziness".to_string(), self.laziness);
+        map.insert("fear".to_string(), self.fear);
+        map.insert("appetite".to_string(), self.appetite);
+        map.insert("libido".to_string(), self.libido);
+        map.insert("separation".to_string(), self.separation);
+        map.insert("alignment".to_string(), self.alignment);
+        map.insert("cohesion".to_string(), self.cohesion);
+        map.insert("sociability".to_string(), self.sociability);
+        map.insert("carnivory".to_string(), self.carnivory);
+        map.insert("selectivity".to_string(), self.selectivity);
+        map.insert("toxin_tolerance".to_string(), self.toxin_tolerance);
+        map.insert("immunity".to_string(), self.immunity);
+        map.insert("memory_capacity".to_string(), self.memory_capacity);
+        map.insert("territory_size".to_string(), self.territory_size);
+        map.insert("parental_investment".to_string(), self.parental_investment);
+        map.insert("alarm_propensity".to_string(), self.alarm_propensity);
 
         map.insert("hunger_rate".to_string(), self.hunger_rate);
+        map.insert("mass".to_string(), self.mass);
         map.insert("health_scale".to_string(), self.health_scale);
         map.insert("movement_speed".to_string(), self.movement_speed);
         map.insert("gestation_duration".to_string(), self.gestation_duration);
+        map.insert("maturity_age".to_string(), self.maturity_age);
+        map.insert("stamina_capacity".to_string(), self.stamina_capacity);
+        map.insert("stamina_recovery".to_string(), self.stamina_recovery);
+        map.insert("sprint_multiplier".to_string(), self.sprint_multiplier);
 
         map
     }
 }
+
+const GROWTH_RATE_RANGE: std::ops::Range<f32> = 0.5..1.5;
+const TOXICITY_RANGE: std::ops::Range<f32> = 0.0..0.3;
+const NUTRITION_RANGE: std::ops::Range<f32> = 0.5..1.5;
+const DISPERSAL_RANGE: std::ops::Range<f32> = 10.0..60.0;
+
+// Growth lost to producing a full dose of toxins, and to packing extra nutrients
+const TOXICITY_COST: f32 = 0.5;
+const NUTRITION_COST: f32 = 0.4;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct PlantGenotype {
+    // Genotype Variables
+    pub growth_rate: f32,
+    pub toxicity: f32,
+    pub nutrition: f32,
+    pub dispersal: f32,
+    // Derived Variables
+    pub growth: f32,
+}
+
+impl PlantGenotype {
+    pub fn new(thread_rng: &mut ThreadRng) -> PlantGenotype {
+        let mut genotype = PlantGenotype {
+            growth_rate: thread_rng.gen_range(GROWTH_RATE_RANGE),
+            toxicity: thread_rng.gen_range(TOXICITY_RANGE),
+            nutrition: thread_rng.gen_range(NUTRITION_RANGE),
+            dispersal: thread_rng.gen_range(DISPERSAL_RANGE),
+            // Derived variables
+            growth: 0.,
+        };
+        genotype.derive_genotype();
+
+        genotype
+    }
+
+    pub fn derive_genotype(&mut self) {
+        // Defences and nutrients both come out of the growth budget
+        self.growth = (self.growth_rate
+            * (1. - self.toxicity * TOXICITY_COST)
+            * (1. - (self.nutrition - 1.) * NUTRITION_COST))
+            .max(0.);
+    }
+
+    // Plants spread asexually, seedlings are mutated copies of their parent
+    pub fn seedling(&self, thread_rng: &mut ThreadRng) -> PlantGenotype {
+        let mut new_genotype = *self;
+        new_genotype.mutate(thread_rng);
+
+        new_genotype
+    }
+
+    pub fn mutate(&mut self, thread_rng: &mut ThreadRng) {
+        self.growth_rate = mutate_gene(self.growth_rate, GROWTH_RATE_RANGE, thread_rng);
+        self.toxicity = mutate_gene(self.toxicity, TOXICITY_RANGE, thread_rng);
+        self.nutrition = mutate_gene(self.nutrition, NUTRITION_RANGE, thread_rng);
+        self.dispersal = mutate_gene(self.dispersal, DISPERSAL_RANGE, thread_rng);
+
+        self.derive_genotype();
+    }
+
+    pub fn to_vec(self) -> Vec<f32> {
+        vec![
+            self.growth_rate,
+            self.toxicity,
+            self.nutrition,
+            self.dispersal,
+            self.growth,
+        ]
+    }
+}
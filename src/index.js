@@ -11,7 +11,9 @@ import * as Renderer from "./renderer.js"
 const WORLD_SETTINGS = {
     wolf_count: 8,
     sheep_count: 256,
-    size: 1024
+    width: 1024,
+    height: 1024,
+    origin: [0, 0]
 }
 
 const STATE_COLOURS = {
@@ -20,8 +22,45 @@ const STATE_COLOURS = {
     2: "yellow", // Fleeing
     3: "green", // Reproducing
     4: "grey", // Dead
+    5: "#6666ff", // Resting
+    6: "orange", // Defending
+    7: "#aa66ff", // Homing
+    8: "#66ddff", // Following
 }
 
+// Portraits and debug colours of the built-in species, other species fall back on their role
+const SPECIES_ICONS = {
+    Wolf: "./rsc/textures/wolf_icon.png",
+    Sheep: "./rsc/textures/sheep_icon.png",
+    Grass: "./rsc/textures/grass_icon.png",
+}
+const ROLE_ICONS = {
+    Animal: "./rsc/textures/sheep_icon.png",
+    Plant: "./rsc/textures/grass_icon.png",
+    Carcass: "./rsc/textures/carcass_icon.svg",
+}
+const SPECIES_COLOURS = {
+    Wolf: "red",
+    Sheep: "#aaaaaa",
+    Grass: "green",
+}
+const ROLE_COLOURS = {
+    Plant: "green",
+    Carcass: "#5a1e14",
+}
+
+// Logged gene averages, in the order of the genotype
+const GENE_STATS = [
+    ["body_size", "Body size"],
+    ["sight_distance", "Sight"],
+    ["muscle_mass", "Strength"],
+    ["hunger_rate", "Hunger rate"],
+    ["health_scale", "Health factor"],
+    ["movement_speed", "Speed"],
+    ["gestation_duration", "Gestation duration"],
+    ["reproduction_chance", "Reproduction chance"],
+]
+
 const cut_to_decimal = (value, decimal) => {
     return Math.round(value * 10 ** decimal) / 10 ** decimal
 }
@@ -29,8 +68,20 @@ const cut_to_decimal = (value, decimal) => {
 class App {
     constructor() {
 
-        this.world = new wasm.World(WORLD_SETTINGS.sheep_count, WORLD_SETTINGS.wolf_count, WORLD_SETTINGS.size)
-        this.renderer = new Renderer.default(WORLD_SETTINGS.sheep_count, WORLD_SETTINGS.wolf_count, WORLD_SETTINGS.size, this.world)
+        this.world = new wasm.World(
+            WORLD_SETTINGS.sheep_count,
+            WORLD_SETTINGS.wolf_count,
+            WORLD_SETTINGS.width,
+            WORLD_SETTINGS.height,
+            WORLD_SETTINGS.origin[0],
+            WORLD_SETTINGS.origin[1]
+        )
+        this.renderer = new Renderer.default(
+            WORLD_SETTINGS.width,
+            WORLD_SETTINGS.height,
+            WORLD_SETTINGS.origin,
+            this.world
+        )
         log(`Simulation world started with seed [${this.world.seed}].`);
         /* log(this.world.get_quadtree()); */
 
@@ -45,44 +96,59 @@ class App {
 
         this.logging_data = {
             time: [],
-            sheep: {
-                count: [],
-                body_size: [],
-                sight_distance: [],
-                muscle_mass: [],
-                hunger_rate: [],
-                health_scale: [],
-                movement_speed: [],
-                gestation_duration: [],
-                reproduction_chance: [],
-                health: [],
-                hunger: [],
-            },
-            wolves: {
-                count: [],
-                body_size: [],
-                sight_distance: [],
-                muscle_mass: [],
-                hunger_rate: [],
-                health_scale: [],
-                movement_speed: [],
-                gestation_duration: [],
-                reproduction_chance: [],
-                health: [],
-                hunger: [],
-            },
-            grass: {
-                count: [],
-                health: []
-            },
         }
-        const selected_categories = ["sheep", "wolves", "grass"]
-        for (let selected_category of selected_categories) {
-            for (let key of Object.keys(this.logging_data[selected_category])) {
-                this.logging_data[selected_category][key].max = 0;
+        this.refreshSpecies()
+        this.update()
+    }
+
+    // Picks up species added since the last call, giving each a stats category
+    refreshSpecies() {
+        this.species = this.world.get_species();
+        let added = false;
+        for (const species of this.species) {
+            if (this.logging_data[species.name]) continue;
+            const stats = ["count", "health"];
+            if (species.role == "Animal") {
+                stats.push("hunger", ...GENE_STATS.map(([stat]) => stat));
+            }
+            this.logging_data[species.name] = {};
+            for (const stat of stats) {
+                // Pad the history so every stat lines up with the time axis
+                const history = new Array(this.logging_data.time.length).fill(0);
+                history.max = 0;
+                this.logging_data[species.name][stat] = history;
             }
+            added = true;
         }
-        this.update()
+        if (added && this.stats_selector) {
+            this.buildStatsSelector();
+        }
+    }
+
+    speciesOf(id) {
+        if (!this.species[id]) {
+            this.refreshSpecies();
+        }
+        return this.species[id];
+    }
+
+    buildStatsSelector() {
+        const selected = this.stats_selector.selectedIndex;
+        this.stats_selector.innerHTML = "";
+        for (const species of this.species) {
+            const group = document.createElement("optgroup");
+            group.label = species.name;
+            group.setAttribute("value", species.name);
+            const labels = Object.fromEntries([["count", "Count"], ["health", "Health"], ["hunger", "Hunger"], ...GENE_STATS]);
+            for (const stat of Object.keys(this.logging_data[species.name])) {
+                const option = document.createElement("option");
+                option.value = stat;
+                option.innerText = labels[stat];
+                group.appendChild(option);
+            }
+            this.stats_selector.appendChild(group);
+        }
+        this.stats_selector.selectedIndex = Math.max(selected, 0);
     }
 
     initListeners() {
@@ -106,8 +172,8 @@ class App {
             this.renderer.mousecaster.setFromCamera(this.renderer.three_mouse, this.renderer.camera);
             const intersects = this.renderer.mousecaster.intersectObject(this.renderer.ground, false);
             if (intersects.length > 0) {
-                this.renderer.mouse.x = intersects[0].point.x + WORLD_SETTINGS.size / 2;
-                this.renderer.mouse.y = intersects[0].point.z + WORLD_SETTINGS.size / 2;
+                this.renderer.mouse.x = intersects[0].point.x + this.renderer.center[0];
+                this.renderer.mouse.y = intersects[0].point.z + this.renderer.center[1];
 
                 const nearby_agents = this.world.get_agents_in_radius(this.renderer.mouse.x, this.renderer.mouse.y, 10)
                 if (nearby_agents.positions.length > 0) {
@@ -277,28 +343,34 @@ class App {
             case 4:
                 stateName = "Dead"
                 break;
-        }
-        let type = "Grass"
-        let imgSrc = "./rsc/textures/grass_icon.png"
-        switch (agents.types[index]) {
-            case 0:
-                type = "Wolf"
-                imgSrc = "./rsc/textures/wolf_icon.png"
+            case 5:
+                stateName = "Resting"
                 break;
-            case 1:
-                type = "Sheep"
-                imgSrc = "./rsc/textures/sheep_icon.png"
+            case 6:
+                stateName = "Defending"
+                break;
+            case 7:
+                stateName = "Homing"
+                break;
+            case 8:
+                stateName = "Following"
                 break;
         }
+        const species = this.speciesOf(agents.types[index])
+        const type = species ? species.name : "Unknown"
+        const role = species ? species.role : "Plant"
+        const imgSrc = SPECIES_ICONS[type] ?? ROLE_ICONS[role]
         this.agent_inspector_title.innerHTML = type;
         this.agent_inspector_stats.innerText = `State: ${stateName}
             \nPosition: ${Math.floor(agents.positions[index][0])},${Math.floor(agents.positions[index][1])}
-            \nHealth: ${Math.floor(agents.vitals[index][0])}
+            \n${role == "Carcass" ? "Meat left" : "Health"}: ${Math.floor(agents.vitals[index][0])}
             \nHunger: ${Math.floor(agents.vitals[index][1])}
             `
-        if (type != "Grass") {
+        if (role == "Animal") {
             this.agent_inspector_stats.innerText +=
                 `
+            \nSex: ${agents.sexes[index] == 1 ? "Female" : "Male"}
+            \nAge: ${Math.floor(agents.ages[index])}
             \nGenes
             \nBody size: ${cut_to_decimal(agents.genotypes[index][0], 3)}
             \nSight range: ${cut_to_decimal(agents.genotypes[index][1], 3)}
@@ -327,6 +399,8 @@ class App {
         this.canvas.width = 1024;
         this.canvas.height = 1024;
         this.canvas.id = "debugCanvas"
+        // Species whose quadtree is drawn
+        this.debug_species = 0
     }
 
     update() {
@@ -354,9 +428,9 @@ class App {
             }
             /* log(index)
             log(this.mousepicked_agent.ids, agents.ids) */
-            this.renderer.selection_circle.position.x = agents.positions[index][0] - WORLD_SETTINGS.size / 2
+            this.renderer.selection_circle.position.x = agents.positions[index][0] - this.renderer.center[0]
             this.renderer.selection_circle.position.y = agents.positions[index][2] * 12.5 + 1.
-            this.renderer.selection_circle.position.z = agents.positions[index][1] - WORLD_SETTINGS.size / 2
+            this.renderer.selection_circle.position.z = agents.positions[index][1] - this.renderer.center[1]
 
             this.renderer.renderer.domElement.style.cursor = "pointer"
             this.renderer.selection_circle.material.color = STATE_COLOURS[agents.states[index]]
@@ -377,18 +451,17 @@ class App {
             this.renderer.camera.userData.scroll = 0;
 
             this.renderer.selection_circle.material.color = STATE_COLOURS[agents.states[index]]
-            this.renderer.selection_circle.position.x = agents.positions[index][0] - (WORLD_SETTINGS.size / 2)
+            this.renderer.selection_circle.position.x = agents.positions[index][0] - this.renderer.center[0]
             this.renderer.selection_circle.position.y = agents.positions[index][2] * 12.5 + 1.
-            this.renderer.selection_circle.position.z = agents.positions[index][1] - WORLD_SETTINGS.size / 2
+            this.renderer.selection_circle.position.z = agents.positions[index][1] - this.renderer.center[1]
 
-            /* this.renderer.camera.position.y = WORLD_SETTINGS.size / 2; */
-            this.renderer.camera.position.x = agents.positions[index][0] - WORLD_SETTINGS.size / 2 + 100 * this.renderer.camera.position.y * .01
-            this.renderer.camera.position.z = agents.positions[index][1] - WORLD_SETTINGS.size / 2 - 100 * this.renderer.camera.position.y * .01
+            this.renderer.camera.position.x = agents.positions[index][0] - this.renderer.center[0] + 100 * this.renderer.camera.position.y * .01
+            this.renderer.camera.position.z = agents.positions[index][1] - this.renderer.center[1] - 100 * this.renderer.camera.position.y * .01
 
             this.renderer.camera.lookAt(
-                agents.positions[index][0] - WORLD_SETTINGS.size / 2,
+                agents.positions[index][0] - this.renderer.center[0],
                 0,
-                agents.positions[index][1] - WORLD_SETTINGS.size / 2
+                agents.positions[index][1] - this.renderer.center[1]
             )
 
             this.updateInspector(index, agents);
@@ -400,13 +473,13 @@ class App {
         }
 
         if (this.canvas && true) {
-            let active_quad = this.world.activate(this.mouse.x, this.mouse.y)
+            let active_quad = this.world.activate(this.debug_species, this.mouse.x, this.mouse.y) ?? {}
             /* if (active_quad) {
                 log(active_quad.name, active_quad.position)
             } */
             active_quad.name = active_quad.name == null ? "none" : active_quad.name
             let then = performance.now();
-            let q = this.world.get_quadtree()
+            let q = this.world.get_quadtree(this.debug_species) ?? []
             /* log(q); */
 
             this.ctx.clearRect(0, 0, this.canvas.width, this.canvas.height)
@@ -511,17 +584,10 @@ class App {
                         radius = 10;
                         /* log(i) */
                     } else {
-                        switch (agents.types[i]) {
-                            case 0: // Wolf
-                                this.ctx.fillStyle = "red"
-                                break;
-                            case 1: // Sheep
-                                this.ctx.fillStyle = "#aaaaaa"
-                                break;
-                            case 2: // Grass
-                                this.ctx.fillStyle = "green"
-                                break;
-                        }
+                        const species = this.speciesOf(agents.types[i]);
+                        this.ctx.fillStyle = SPECIES_COLOURS[species.name]
+                            ?? ROLE_COLOURS[species.role]
+                            ?? hexPalette[species.id % hexPalette.length];
                     }
 
                     this.ctx.beginPath();
@@ -629,109 +695,40 @@ class App {
     }
 
     logData(agents) {
-
-        let wolves = {
-            body_size_tally: 0,
-            sight_tally: 0,
-            muscle_tally: 0,
-            hunger_tally: 0,
-            hunger_scale_tally: 0,
-            health_tally: 0,
-            health_scale_tally: 0,
-            speed_tally: 0,
-            gestation_tally: 0,
-            reproduction_chance_tally: 0,
-
-            count: 0
-        }
-
-        let sheep = {
-            body_size_tally: 0,
-            sight_tally: 0,
-            muscle_tally: 0,
-            hunger_tally: 0,
-            hunger_scale_tally: 0,
-            health_tally: 0,
-            health_scale_tally: 0,
-            speed_tally: 0,
-            gestation_tally: 0,
-            reproduction_chance_tally: 0,
-
-            count: 0
+        if (agents.types.some(type => !this.species[type])) {
+            this.refreshSpecies();
         }
-
-        let grass = {
+        const tallies = this.species.map(() => ({
             count: 0,
-            health_tally: 0
-        }
+            health: 0,
+            hunger: 0,
+            genes: new Array(GENE_STATS.length).fill(0),
+        }))
 
         for (let i = 0; i < agents.positions.length; i++) {
-
-            switch (agents.types[i]) {
-                case 0:
-                    wolves.count++;
-                    wolves.body_size_tally += agents.genotypes[i][0];
-                    wolves.sight_tally += agents.genotypes[i][1];
-                    wolves.muscle_tally += agents.genotypes[i][2];
-                    wolves.hunger_scale_tally += agents.genotypes[i][3];
-                    wolves.health_scale_tally += agents.genotypes[i][4];
-                    wolves.speed_tally += agents.genotypes[i][5];
-                    wolves.gestation_tally += agents.genotypes[i][6];
-                    wolves.health_tally += agents.vitals[i][0]
-                    wolves.hunger_tally += agents.vitals[i][1];
-                    wolves.reproduction_chance_tally += agents.genotypes[i][7]
-                    break;
-                case 1:
-                    sheep.count++;
-                    sheep.body_size_tally += agents.genotypes[i][0];
-                    sheep.sight_tally += agents.genotypes[i][1];
-                    sheep.muscle_tally += agents.genotypes[i][2];
-                    sheep.hunger_scale_tally += agents.genotypes[i][3];
-                    sheep.health_scale_tally += agents.genotypes[i][4];
-                    sheep.speed_tally += agents.genotypes[i][5];
-                    sheep.gestation_tally += agents.genotypes[i][6];
-                    sheep.health_tally += agents.vitals[i][0]
-                    sheep.hunger_tally += agents.vitals[i][1];
-                    sheep.reproduction_chance_tally += agents.genotypes[i][7];
-                    break;
-                case 2:
-                    grass.count++;
-                    grass.health_tally += agents.vitals[i][0]
-                    break;
+            const tally = tallies[agents.types[i]];
+            tally.count++;
+            tally.health += agents.vitals[i][0];
+            tally.hunger += agents.vitals[i][1];
+            for (let gene = 0; gene < GENE_STATS.length && gene < agents.genotypes[i].length; gene++) {
+                tally.genes[gene] += agents.genotypes[i][gene];
             }
         }
 
-        this.logging_data.sheep.count.push(sheep.count);
-        this.logging_data.wolves.count.push(wolves.count);
-        this.logging_data.grass.count.push(grass.count);
-
-        this.logging_data.sheep.body_size.push(sheep.body_size_tally / sheep.count);
-        this.logging_data.sheep.sight_distance.push(sheep.sight_tally / sheep.count);
-        this.logging_data.sheep.muscle_mass.push(sheep.muscle_tally / sheep.count);
-        this.logging_data.sheep.hunger.push(sheep.hunger_tally / sheep.count);
-        this.logging_data.sheep.hunger_rate.push(sheep.hunger_scale_tally / sheep.count);
-        this.logging_data.sheep.health.push(sheep.health_tally / sheep.count)
-        this.logging_data.sheep.health_scale.push(sheep.health_scale_tally / sheep.count);
-        this.logging_data.sheep.movement_speed.push(sheep.speed_tally / sheep.count);
-        this.logging_data.sheep.gestation_duration.push(sheep.gestation_tally / sheep.count);
-        this.logging_data.sheep.reproduction_chance.push(sheep.reproduction_chance_tally / sheep.count);
-
-        this.logging_data.wolves.body_size.push(wolves.body_size_tally / wolves.count);
-        this.logging_data.wolves.sight_distance.push(wolves.sight_tally / wolves.count);
-        this.logging_data.wolves.muscle_mass.push(wolves.muscle_tally / wolves.count);
-        this.logging_data.wolves.hunger.push(wolves.hunger_tally / wolves.count);
-        this.logging_data.wolves.hunger_rate.push(wolves.hunger_scale_tally / wolves.count);
-        this.logging_data.wolves.health.push(wolves.health_tally / wolves.count)
-        this.logging_data.wolves.health_scale.push(wolves.health_scale_tally / wolves.count);
-        this.logging_data.wolves.movement_speed.push(wolves.speed_tally / wolves.count);
-        this.logging_data.wolves.gestation_duration.push(wolves.gestation_tally / wolves.count);
-        this.logging_data.wolves.reproduction_chance.push(wolves.reproduction_chance_tally / wolves.count);
-
-        this.logging_data.grass.health.push(grass.health_tally / grass.count);
+        for (const species of this.species) {
+            const tally = tallies[species.id];
+            const data = this.logging_data[species.name];
+            data.count.push(tally.count);
+            data.health.push(tally.health / tally.count);
+            if (data.hunger) {
+                data.hunger.push(tally.hunger / tally.count);
+                GENE_STATS.forEach(([stat], gene) => data[stat].push(tally.genes[gene] / tally.count));
+            }
+        }
 
         this.logging_data.time.push(this.time);
 
-        const sub_indexes = ["wolves", "sheep", "grass"]
+        const sub_indexes = this.species.map(species => species.name)
         if (this.logging_data.time.length > 1200) {
             sub_indexes.forEach(sub_index => {
                 Object.keys(this.logging_data[sub_index]).forEach(key => {
@@ -2,10 +2,50 @@ mod quadtree;
 use quadtree::QuadTree;
 
 mod genes;
-use genes::Genotype;
+use genes::{Genotype, PlantGenotype};
 
 mod agent;
-use agent::{Agent, AgentType, State};
+use agent::{Agent, AgentType, DeathCause, Sex, State};
+
+mod terrain;
+use terrain::{Obstacle, Terrain};
+
+mod environment;
+use environment::Clock;
+
+mod metabolism;
+use metabolism::Metabolism;
+
+mod behaviour;
+use behaviour::{BehaviourSet, Context};
+
+mod brain;
+use brain::BrainMode;
+
+mod neat;
+use neat::Neat;
+
+mod fields;
+use fields::{Fields, FEAR_DEPOSIT, SCENT_DEPOSIT};
+
+mod memory;
+
+mod care;
+
+mod pack;
+
+mod disease;
+use disease::{Pathogen, PathogenDefinition};
+
+mod territory;
+
+mod response;
+use response::{FunctionalResponse, ResponseLog};
+
+mod signals;
+
+mod species;
+use species::{Role, SpeciesDefinition, SpeciesId, SpeciesRegistry};
 
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
@@ -36,6 +76,44 @@ extern "C" {
 #[wasm_bindgen]
 pub struct SerializedVector2(f32, f32);
 
+// Axis aligned rectangle describing the extent of the world
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Bounds {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+impl Bounds {
+    pub fn new(origin: (f32, f32), width: f32, height: f32) -> Bounds {
+        Bounds {
+            x: origin.0,
+            y: origin.1,
+            width,
+            height,
+        }
+    }
+
+    pub fn max_x(&self) -> f32 {
+        self.x + self.width
+    }
+
+    pub fn max_y(&self) -> f32 {
+        self.y + self.height
+    }
+
+    pub fn contains(&self, point: (f32, f32)) -> bool {
+        point.0 >= self.x && point.0 <= self.max_x() && point.1 >= self.y && point.1 <= self.max_y()
+    }
+
+    pub fn random_point(&self, rng: &mut ThreadRng) -> (f32, f32) {
+        (
+            self.x + rng.gen::<f32>() * self.width,
+            self.y + rng.gen::<f32>() * self.height,
+        )
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct SerializedQuadTree {
     pub locations: Vec<(f32, f32)>,
@@ -62,18 +140,25 @@ impl SerializedQuadTree {
 
 #[wasm_bindgen]
 pub struct World {
-    wolf_quad: QuadTree,
-    sheep_quad: QuadTree,
-    grass_quad: QuadTree,
-    
-    size: f32,
+    quads: Vec<QuadTree>,
+
+    bounds: Bounds,
+    terrain: Terrain,
+    clock: Clock,
+    metabolism: Metabolism,
+    species: SpeciesRegistry,
+    pathogens: Vec<Pathogen>,
+    fields: Fields,
+    behaviours: BehaviourSet,
+    brain_mode: BrainMode,
+    neat: Neat,
     pub seed: u32,
     rng: ThreadRng,
-    sheep_num: usize,
-    wolf_num: usize,
     noise: noise::OpenSimplex,
     agents: HashMap<Uuid, Agent>,
     to_remove: Vec<Uuid>,
+    deaths: HashMap<String, usize>,
+    responses: ResponseLog,
     optimized_query: bool,
 }
 
@@ -83,32 +168,48 @@ const MAX_GRASS: usize = 1024;
 const MAX_CHILDREN: usize = 16;
 const MAX_LEVELS: usize = 6;
 const NOISE_SCALING: f64 = 0.01;
+const INITIAL_AGE_SPREAD: f32 = 0.5;
 
 // Main JS interface to the simulation
 #[wasm_bindgen(inspectable)]
 impl World {
     #[wasm_bindgen(constructor)]
-    pub fn new(sheep_num: usize, wolf_num: usize, size: f32) -> World {
+    pub fn new(
+        sheep_num: usize,
+        wolf_num: usize,
+        width: f32,
+        height: f32,
+        origin_x: f32,
+        origin_y: f32,
+    ) -> World {
         console_error_panic_hook::set_once();
 
         let mut rng = rand::thread_rng();
         let seed = rng.gen();
+        let bounds = Bounds::new((origin_x, origin_y), width, height);
+        let species = SpeciesRegistry::new(sheep_num, wolf_num, MAX_GRASS);
         let mut w = World {
-            wolf_quad: QuadTree::new(size),
-            sheep_quad: QuadTree::new(size),
-            grass_quad: QuadTree::new(size),
-    
-            size,
+            quads: Vec::new(),
+
+            bounds,
+            terrain: Terrain::new(bounds),
+            clock: Clock::new(),
+            metabolism: Metabolism::new(),
+            fields: Fields::new(bounds, species.species.len()),
+            species,
+            pathogens: Vec::new(),
+            behaviours: BehaviourSet::new(),
+            brain_mode: BrainMode::Scripted,
+            neat: Neat::new(),
             seed,
             rng: rand::thread_rng(),
 
-            sheep_num,
-            wolf_num,
-
             noise: OpenSimplex::new(seed),
 
             agents: HashMap::with_capacity(sheep_num + wolf_num + MAX_GRASS),
             to_remove: Vec::new(),
+            deaths: HashMap::new(),
+            responses: ResponseLog::new(),
             optimized_query: true,
         };
         w.spawn_entities();
@@ -118,14 +219,36 @@ impl World {
 
     #[wasm_bindgen]
     pub fn step(&mut self, optimized: bool, time: f32) {
+        self.clock.update(time);
         self.build_quadtree_good();
 
         self.update_agents(optimized, time);
+        self.fields.update();
+        if self.brain_mode == BrainMode::Neat {
+            self.neat.update(&self.agents, time, &mut self.rng);
+        }
     }
 
     fn update_agents(&mut self, optimized: bool, time: f32) {
         /* log(&self.agents.len().to_string()); */
         let mut old_agents = self.agents.clone();
+        let sight_multiplier = self.clock.sight_multiplier();
+        // Plant species stop seeding once they reach their population
+        let mut plant_counts = vec![0; self.species.species.len()];
+        let mut populations = vec![0; self.species.species.len()];
+        for agent in self.agents.values() {
+            if agent.kind.is_plant() {
+                plant_counts[agent.kind.species()] += 1;
+            }
+            if agent.kind.is_animal() && !agent.dead {
+                populations[agent.kind.species()] += 1;
+            }
+        }
+        let prey_densities = response::prey_densities(
+            &self.species,
+            &populations,
+            self.bounds.width * self.bounds.height,
+        );
         for (id, agent) in old_agents.iter_mut() {
             /* log(&format!(
                 "{}:{:?}",
@@ -134,67 +257,114 @@ impl World {
             )); */
             let mut current_agent = self.agents.get(id).unwrap().clone();
             if current_agent.dead {
-                self.to_remove.push(*id);
+                if current_agent.kind.is_animal() {
+                    if let Some(cause) = current_agent.death_cause {
+                        *self
+                            .deaths
+                            .entry(format!(
+                                "{}:{}",
+                                self.species.name(&current_agent.kind),
+                                cause
+                            ))
+                            .or_insert(0) += 1;
+                        // Kill sites scare off the victim's kind for a while
+                        if cause == DeathCause::Predation {
+                            self.fields.fear[current_agent.kind.species()].deposit(
+                                (current_agent.position.0, current_agent.position.1),
+                                FEAR_DEPOSIT,
+                            );
+                        }
+                    }
+                    // Dead animals stay around as carcasses until they rot away
+                    current_agent.become_carcass();
+                    self.agents.insert(*id, current_agent);
+                } else {
+                    self.to_remove.push(*id);
+                }
                 continue;
             };
 
             match agent.kind {
-                AgentType::Sheep(genotype) => {
+                AgentType::Animal(species, ref genotype) => {
+                    let genotype = genotype.at_age(time - current_agent.born);
+                    // Only look at the species this animal cares about
                     let mut nearby_agents = Vec::new();
-                    nearby_agents = self.wolf_quad.get_children_in_radius(
-                        (agent.position.0, agent.position.1),
-                        genotype.sight_distance,
-                        nearby_agents,
-                    );
-                    nearby_agents = self.grass_quad.get_children_in_radius(
-                        (agent.position.0, agent.position.1),
-                        genotype.sight_distance,
-                        nearby_agents,
-                    );
+                    for visible in self.species.visible_to(species) {
+                        nearby_agents = self.quads[visible].get_children_in_radius(
+                            (agent.position.0, agent.position.1),
+                            genotype.sight_distance * sight_multiplier,
+                            nearby_agents,
+                        );
+                    }
 
+                    let kills = current_agent.kills;
+                    let context = Context {
+                        nearby_agents: &nearby_agents,
+                        agents: &self.agents,
+                        genotype,
+                        noise: &self.noise,
+                        terrain: &self.terrain,
+                        clock: &self.clock,
+                        metabolism: &self.metabolism,
+                        species: &self.species,
+                        pathogens: &self.pathogens,
+                        fields: &self.fields,
+                        time,
+                    };
                     // Pass a non mutable reeference, return mutations to the agents hashmaps
                     let modified_agents = current_agent.update(
-                        nearby_agents,
-                        &self.agents,
-                        genotype,
-                        &self.noise,
+                        &context,
                         &mut self.rng,
-                        time,
+                        &self.bounds,
+                        &self.behaviours,
+                        if self.brain_mode == BrainMode::Neat {
+                            Some(&mut self.neat)
+                        } else {
+                            None
+                        },
                     );
                     for (id, agent) in modified_agents {
                         self.agents.insert(id, agent);
                     }
-                    /* if let AgentType::Sheep(_) = agent.kind {
-                        log(&format!("{:?}, {:?}", agent.position, agent.acceleration));
-                    } */
-                }
-                AgentType::Wolf(genotype) => {
-                    let mut nearby_agents = Vec::new();
-                    nearby_agents = self.sheep_quad.get_children_in_radius(
-                        (agent.position.0, agent.position.1),
-                        genotype.sight_distance,
-                        nearby_agents,
-                    );
-                    let modified_agents = current_agent.update(
-                        nearby_agents,
-                        &self.agents,
-                        genotype,
-                        &self.noise,
-                        &mut self.rng,
-                        time,
+                    if let Some(density) = prey_densities[species] {
+                        self.responses
+                            .record(species, density, current_agent.kills - kills);
+                    }
+                    self.fields.scent[species].deposit(
+                        (current_agent.position.0, current_agent.position.1),
+                        SCENT_DEPOSIT,
                     );
-                    for (id, agent) in modified_agents {
-                        self.agents.insert(id, agent);
+                }
+                AgentType::Plant(species, genotype) => {
+                    if current_agent.health <= 0. {
+                        self.to_remove.push(*id);
+                        current_agent.dead = true;
+                        current_agent.state = State::Dead;
+                    }
+                    current_agent.health = (current_agent.health
+                        + agent::PLANT_GROWTH_RATE
+                            * self.clock.growth_multiplier()
+                            * genotype.growth)
+                        .min(100.);
+
+                    if !current_agent.dead
+                        && current_agent.health >= agent::SEEDING_HEALTH
+                        && plant_counts[species] < self.species.get(species).population
+                        && self.rng.gen::<f32>()
+                            < agent::SEEDING_CHANCE * self.clock.growth_multiplier()
+                    {
+                        let seedling = self.seedling(&current_agent, species, genotype);
+                        self.agents.insert(seedling.id, seedling);
+                        plant_counts[species] += 1;
                     }
                 }
-                AgentType::Grass() => {
+                AgentType::Carcass() => {
+                    current_agent.health -= agent::CARCASS_DECAY_RATE;
                     if current_agent.health <= 0. {
                         self.to_remove.push(*id);
                         current_agent.dead = true;
                         current_agent.state = State::Dead;
                     }
-                    current_agent.health =
-                        (current_agent.health + agent::PLANT_GROWTH_RATE).min(100.);
                 }
             }
 
@@ -208,26 +378,35 @@ impl World {
         self.to_remove.clear();
     }
 
+    // New plant dropped somewhere within the parent's dispersal distance
+    fn seedling(&mut self, parent: &Agent, species: SpeciesId, genotype: PlantGenotype) -> Agent {
+        let angle = self.rng.gen::<f32>() * std::f32::consts::PI * 2.;
+        let distance = self.rng.gen::<f32>() * genotype.dispersal.max(0.);
+        let position = self.terrain.resolve_collision((
+            (parent.position.0 + angle.cos() * distance).clamp(self.bounds.x, self.bounds.max_x()),
+            (parent.position.1 + angle.sin() * distance).clamp(self.bounds.y, self.bounds.max_y()),
+        ));
+        Agent::new(
+            AgentType::Plant(species, genotype.seedling(&mut self.rng)),
+            position,
+            Uuid::new_v4(),
+            self.rng.gen::<f64>(),
+            self.clock.time,
+        )
+    }
+
     fn build_quadtree_good(&mut self) {
-        self.wolf_quad = QuadTree::new(self.size);
-        self.sheep_quad = QuadTree::new(self.size);
-        self.grass_quad = QuadTree::new(self.size);
+        // One spatial index per species, species can be added while running
+        self.quads = self
+            .species
+            .species
+            .iter()
+            .map(|_| QuadTree::covering(&self.bounds))
+            .collect();
 
         for (id, agent) in self.agents.iter() {
-            match agent.kind {
-                AgentType::Wolf(_) => {
-                    self.wolf_quad
-                        .insert((*id, (agent.position.0, agent.position.1)), &self.agents);
-                }
-                AgentType::Sheep(_) => {
-                    self.sheep_quad
-                        .insert((*id, (agent.position.0, agent.position.1)), &self.agents);
-                }
-                AgentType::Grass() => {
-                    self.grass_quad
-                        .insert((*id, (agent.position.0, agent.position.1)), &self.agents);
-                }
-            }
+            self.quads[agent.kind.species()]
+                .insert((*id, (agent.position.0, agent.position.1)), &self.agents);
         }
     }
 
@@ -235,7 +414,7 @@ impl World {
     pub fn test(&self) -> u32 {
         let agent_list = HashMap::new();
 
-        let mut q = QuadTree::new(1024.);
+        let mut q = QuadTree::new((0., 0.), 1024.);
         q.subdivide(&agent_list);
         q.child_nodes[0].subdivide(&agent_list);
         q.child_nodes[0].child_nodes[3].subdivide(&agent_list);
@@ -273,9 +452,13 @@ impl World {
         result
     }
 
+    // Every quad of the tree holding a species
     #[wasm_bindgen]
-    pub fn get_quadtree(&self) -> JsValue {
-        let result = self.sheep_quad.get_all();
+    pub fn get_quadtree(&self, species: usize) -> JsValue {
+        let result = match self.quads.get(species) {
+            Some(quad) => quad.get_all(),
+            None => return JsValue::NULL,
+        };
         /* log(&format!("{:?}", result)); */
 
         /* let mut result = SerializedQuadTree::new();
@@ -285,44 +468,46 @@ impl World {
     }
 
     fn spawn_entities(&mut self) {
-        let mut rng = rand::thread_rng();
-        for _ in 0..self.wolf_num {
-            let id = Uuid::new_v4();
-            self.agents.insert(
-                id,
-                Agent::new(
-                    AgentType::Wolf(Genotype::new(&mut self.rng)),
-                    (rng.gen::<f32>() * self.size, rng.gen::<f32>() * self.size),
-                    id,
-                    rng.gen::<f64>(),
-                ),
-            );
-        }
-
-        for _ in 0..self.sheep_num {
-            let id = Uuid::new_v4();
-            self.agents.insert(
-                id,
-                Agent::new(
-                    AgentType::Sheep(Genotype::new(&mut self.rng)),
-                    (rng.gen::<f32>() * self.size, rng.gen::<f32>() * self.size),
-                    id,
-                    rng.gen::<f64>(),
-                ),
-            );
+        for id in 0..self.species.species.len() {
+            self.spawn_species(id, self.species.get(id).population);
         }
+    }
 
-        for _ in 0..MAX_GRASS {
+    fn spawn_species(&mut self, species: SpeciesId, count: usize) {
+        let mut rng = rand::thread_rng();
+        for _ in 0..count {
             let id = Uuid::new_v4();
-            self.agents.insert(
-                id,
-                Agent::new(
-                    AgentType::Grass(),
-                    (rng.gen::<f32>() * self.size, rng.gen::<f32>() * self.size),
+            let agent = match self.species.get(species).role {
+                Role::Animal => {
+                    let genotype =
+                        Genotype::from_schema(&self.species.get(species).genes, &mut self.rng);
+                    let mut agent = Agent::new(
+                        AgentType::Animal(species, genotype.clone()),
+                        self.bounds.random_point(&mut rng),
+                        id,
+                        rng.gen::<f64>(),
+                        0.,
+                    );
+                    // Start with a mix of ages so the founders don't all die of old age together
+                    agent.born = -rng.gen::<f32>() * genotype.max_lifespan * INITIAL_AGE_SPREAD;
+                    agent.life = -agent.born;
+                    agent.health = genotype.health_scale * genotype.growth(agent.life);
+                    if self.brain_mode == BrainMode::Neat {
+                        agent.neat = Some(self.neat.seed_genome(&mut rng));
+                    }
+                    agent
+                }
+                Role::Plant => Agent::new(
+                    AgentType::Plant(species, PlantGenotype::new(&mut self.rng)),
+                    self.bounds.random_point(&mut rng),
                     id,
                     rng.gen::<f64>(),
+                    0.,
                 ),
-            );
+                // Carcasses only come from dead animals
+                Role::Carcass => return,
+            };
+            self.agents.insert(id, agent);
         }
     }
 
@@ -346,18 +531,32 @@ impl World {
             result.accelerations.push(agent.acceleration);
             result.types.push(agent.kind.to_int());
             match agent.kind {
-                AgentType::Sheep(genotype) => {
+                AgentType::Animal(_, ref genotype) => {
                     result.genotypes.push(genotype.to_vec());
+                    result.growth.push(genotype.growth(agent.life));
                 }
-                AgentType::Wolf(genotype) => {
+                AgentType::Plant(_, genotype) => {
                     result.genotypes.push(genotype.to_vec());
+                    result.growth.push(1.);
                 }
-                AgentType::Grass() => {
+                AgentType::Carcass() => {
                     result.genotypes.push(Vec::new());
+                    result.growth.push(1.);
                 }
             }
             result.states.push(agent.state.to_int());
-            result.vitals.push((agent.health, agent.hunger))
+            result
+                .vitals
+                .push((agent.health, agent.hunger, agent.stamina));
+            result.ages.push(agent.life);
+            result.sexes.push(agent.sex.to_int());
+            result
+                .infections
+                .push(disease::stage_to_int(&agent.infections));
+            result.pregnancies.push(match &agent.pregnancy {
+                Some(pregnancy) => pregnancy.progress(self.clock.time),
+                None => 0.,
+            });
         }
 
         // Delete marked agents after having sent them in a dead state
@@ -368,8 +567,9 @@ impl World {
         serde_wasm_bindgen::to_value(&result).unwrap()
     }
 
+    // Deepest quad of a species' tree under the mouse
     #[wasm_bindgen]
-    pub fn activate(&self, mouse_x: f32, mouse_y: f32) -> JsValue {
+    pub fn activate(&self, species: usize, mouse_x: f32, mouse_y: f32) -> JsValue {
         /* return serde_wasm_bindgen::to_value(
             &self
                 .quad
@@ -379,8 +579,9 @@ impl World {
         .unwrap(); */
 
         match self
-            .sheep_quad
-            .find_quad_containing_point((mouse_x, mouse_y))
+            .quads
+            .get(species)
+            .and_then(|quad| quad.find_quad_containing_point((mouse_x, mouse_y)))
         {
             Some(q) => serde_wasm_bindgen::to_value(q).unwrap(),
             None => wasm_bindgen::JsValue::NULL,
@@ -391,17 +592,9 @@ impl World {
     pub fn get_agents_in_radius(&self, x: f32, y: f32, radius: f32) -> JsValue {
         let mut result = SerializedAgents::new();
         let mut agents_in_radius = Vec::new();
-        agents_in_radius = self
-            .sheep_quad
-            .get_children_in_radius((x, y), radius, agents_in_radius);
-
-        agents_in_radius = self
-            .wolf_quad
-            .get_children_in_radius((x, y), radius, agents_in_radius);
-
-        agents_in_radius = self
-            .grass_quad
-            .get_children_in_radius((x, y), radius, agents_in_radius);
+        for quad in self.quads.iter() {
+            agents_in_radius = quad.get_children_in_radius((x, y), radius, agents_in_radius);
+        }
 
         for agent in agents_in_radius {
             result.ids.push(agent.0.to_string());
@@ -410,7 +603,7 @@ impl World {
             match a {
                 Some(a) => {
                     result.types.push(a.kind.to_int());
-                    result.vitals.push((a.health, a.hunger));
+                    result.vitals.push((a.health, a.hunger, a.stamina));
                 }
                 None => {}
             }
@@ -420,6 +613,328 @@ impl World {
         serde_wasm_bindgen::to_value(&result).unwrap()
     }
 
+    #[wasm_bindgen]
+    pub fn add_rock(&mut self, x: f32, y: f32, radius: f32) {
+        self.add_obstacle(Obstacle::Rock {
+            position: (x, y),
+            radius,
+        });
+    }
+
+    #[wasm_bindgen]
+    pub fn add_fence(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32) {
+        self.add_obstacle(Obstacle::Fence {
+            start: (x1, y1),
+            end: (x2, y2),
+            thickness,
+        });
+    }
+
+    // Takes a flat list of coordinates [x0, y0, x1, y1, ...]
+    #[wasm_bindgen]
+    pub fn add_polygon(&mut self, points: Vec<f32>) {
+        let points: Vec<(f32, f32)> = points.chunks_exact(2).map(|p| (p[0], p[1])).collect();
+        if points.len() < 3 {
+            return;
+        }
+        self.add_obstacle(Obstacle::Polygon { points });
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_obstacles(&mut self) {
+        self.terrain.clear();
+    }
+
+    #[wasm_bindgen]
+    pub fn get_obstacles(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.terrain.obstacles).unwrap()
+    }
+
+    fn add_obstacle(&mut self, obstacle: Obstacle) {
+        self.terrain.add_obstacle(obstacle);
+        // Make sure nothing is left stuck inside the new obstacle
+        for agent in self.agents.values_mut() {
+            let resolved = self
+                .terrain
+                .resolve_collision((agent.position.0, agent.position.1));
+            agent.position.0 = resolved.0;
+            agent.position.1 = resolved.1;
+        }
+    }
+
+    // Sets how a species' kill rate responds to prey density from { kind, attack_rate,
+    // handling_time, satiation }, kind is Linear, Saturating or Sigmoid
+    #[wasm_bindgen]
+    pub fn set_functional_response(
+        &mut self,
+        species: &str,
+        response: JsValue,
+    ) -> Result<(), JsValue> {
+        let response: FunctionalResponse = serde_wasm_bindgen::from_value(response)?;
+        match self.species.find(species) {
+            Some(id) => {
+                self.species.species[id].response = response;
+                Ok(())
+            }
+            None => Err(JsValue::from_str("Unknown species")),
+        }
+    }
+
+    // Kills per predator per step at each prey density seen so far, for every predator
+    #[wasm_bindgen]
+    pub fn get_functional_responses(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.responses.statistics(&self.species)).unwrap()
+    }
+
+    // Starts recording kills afresh, e.g. after changing a functional response
+    #[wasm_bindgen]
+    pub fn clear_functional_responses(&mut self) {
+        self.responses.clear();
+    }
+
+    // Death counts keyed by "Species:Cause"
+    #[wasm_bindgen]
+    pub fn get_death_statistics(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.deaths).unwrap()
+    }
+
+    // Number of (males, females) alive for each animal species
+    #[wasm_bindgen]
+    pub fn get_sex_ratio(&self) -> JsValue {
+        let mut result: HashMap<String, (usize, usize)> = HashMap::new();
+        for agent in self.agents.values() {
+            if !agent.kind.is_animal() || agent.dead {
+                continue;
+            }
+            let counts = result
+                .entry(self.species.name(&agent.kind).to_string())
+                .or_insert((0, 0));
+            match agent.sex {
+                Sex::Male => counts.0 += 1,
+                Sex::Female => counts.1 += 1,
+            }
+        }
+        serde_wasm_bindgen::to_value(&result).unwrap()
+    }
+
+    // 0 runs the hand written behaviours, 1 the fixed topology networks and 2 the NEAT brains
+    #[wasm_bindgen]
+    pub fn set_brain_mode(&mut self, mode: u8) {
+        self.brain_mode = BrainMode::from_int(mode);
+        self.behaviours = BehaviourSet::for_mode(self.brain_mode);
+        // Animals start out with minimal networks when NEAT is switched on, and lose them when
+        // it's switched off so they don't keep restricting who mates with whom
+        for agent in self.agents.values_mut() {
+            if self.brain_mode != BrainMode::Neat {
+                agent.neat = None;
+            } else if agent.kind.is_animal() && !agent.dead && agent.neat.is_none() {
+                agent.neat = Some(self.neat.seed_genome(&mut self.rng));
+            }
+        }
+        if self.brain_mode != BrainMode::Neat {
+            self.neat.clear();
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn brain_mode(&self) -> u8 {
+        self.brain_mode.to_int()
+    }
+
+    // Turns dens and territory defence on or off for a species, animals give up their dens
+    // when it's switched off
+    #[wasm_bindgen]
+    pub fn set_territorial(&mut self, species: usize, territorial: bool) {
+        if species >= self.species.species.len() {
+            return;
+        }
+        self.species.species[species].behaviour.territorial = territorial;
+        if !territorial {
+            for agent in self.agents.values_mut() {
+                if agent.kind.is_animal() && agent.kind.species() == species {
+                    agent.home = None;
+                }
+            }
+        }
+    }
+
+    // Registers a new species from { name, role, diet, predators, behaviour, genes, population }
+    // and spawns its starting population, returns the new species id
+    #[wasm_bindgen]
+    pub fn add_species(&mut self, definition: JsValue) -> Result<usize, JsValue> {
+        let definition: SpeciesDefinition = serde_wasm_bindgen::from_value(definition)?;
+        let id = self
+            .species
+            .register(definition)
+            .map_err(|error| JsValue::from_str(&error))?;
+        self.fields.resize(self.species.species.len());
+        self.spawn_species(id, self.species.get(id).population);
+        Ok(id)
+    }
+
+    // Adds a species to the diet of another with the given preference, both given by name
+    #[wasm_bindgen]
+    pub fn add_prey(&mut self, predator: &str, prey: &str, preference: f32) -> Result<(), JsValue> {
+        match (self.species.find(predator), self.species.find(prey)) {
+            (Some(predator), Some(prey)) => {
+                self.species.add_prey(predator, prey, preference);
+                Ok(())
+            }
+            _ => Err(JsValue::from_str("Unknown species")),
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn spawn(&mut self, species: usize, count: usize) {
+        if species < self.species.species.len() {
+            self.spawn_species(species, count);
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn get_species(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.species.species).unwrap()
+    }
+
+    // Scent grid left behind by a species, row major from the world origin
+    #[wasm_bindgen]
+    pub fn get_scent_field(&self, species: usize) -> JsValue {
+        match self.fields.scent.get(species) {
+            Some(field) => serde_wasm_bindgen::to_value(&field.serialize()).unwrap(),
+            None => JsValue::NULL,
+        }
+    }
+
+    // Grid of how dangerous each place is for a species after recent kills
+    #[wasm_bindgen]
+    pub fn get_fear_field(&self, species: usize) -> JsValue {
+        match self.fields.fear.get(species) {
+            Some(field) => serde_wasm_bindgen::to_value(&field.serialize()).unwrap(),
+            None => JsValue::NULL,
+        }
+    }
+
+    // Registers a pathogen from { name, hosts, transmissibility, virulence, incubation,
+    // infectious_period, immunity_duration }, hosts are species names
+    #[wasm_bindgen]
+    pub fn add_pathogen(&mut self, definition: JsValue) -> Result<usize, JsValue> {
+        let definition: PathogenDefinition = serde_wasm_bindgen::from_value(definition)?;
+        let mut hosts = Vec::new();
+        for host in definition.hosts.iter() {
+            match self.species.find(host) {
+                Some(id) => hosts.push(id),
+                None => return Err(JsValue::from_str(&format!("Unknown species {}", host))),
+            }
+        }
+        let id = self.pathogens.len();
+        self.pathogens.push(Pathogen {
+            id,
+            name: definition.name,
+            hosts,
+            transmissibility: definition.transmissibility.clamp(0., 1.),
+            virulence: definition.virulence.max(0.),
+            incubation: definition.incubation.max(0.),
+            infectious_period: definition.infectious_period.max(0.),
+            immunity_duration: definition.immunity_duration.max(0.),
+        });
+        Ok(id)
+    }
+
+    // Infects a number of random hosts to start an outbreak
+    #[wasm_bindgen]
+    pub fn infect(&mut self, pathogen: usize, count: usize) {
+        if let Some(pathogen) = self.pathogens.get(pathogen) {
+            disease::seed_infection(&mut self.agents, pathogen, count, self.clock.time);
+        }
+    }
+
+    // Susceptible, exposed, infectious and recovered counts for every pathogen
+    #[wasm_bindgen]
+    pub fn get_disease_statistics(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&disease::statistics(&self.agents, &self.pathogens)).unwrap()
+    }
+
+    // Alarm callers, calls made and mean calling propensity of every animal species
+    #[wasm_bindgen]
+    pub fn get_alarm_statistics(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&signals::statistics(
+            &self.agents,
+            &self.species,
+            self.clock.time,
+        ))
+        .unwrap()
+    }
+
+    // Pack counts and kills of pack hunters against lone wolves
+    #[wasm_bindgen]
+    pub fn get_pack_statistics(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&pack::statistics(&self.agents, &self.species)).unwrap()
+    }
+
+    // Size and average structure of every living NEAT species
+    #[wasm_bindgen]
+    pub fn get_neat_species(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.neat.statistics(&self.agents)).unwrap()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_behaviours(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.behaviours.names()).unwrap()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_environment(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.clock.serialize()).unwrap()
+    }
+
+    #[wasm_bindgen]
+    pub fn set_day_length(&mut self, day_length: f32) {
+        self.clock.day_length = day_length.max(1.);
+    }
+
+    #[wasm_bindgen]
+    pub fn set_days_per_year(&mut self, days_per_year: f32) {
+        self.clock.days_per_year = days_per_year.max(1.);
+    }
+
+    #[wasm_bindgen]
+    pub fn set_cycles_enabled(&mut self, enabled: bool) {
+        self.clock.cycles_enabled = enabled;
+    }
+
+    #[wasm_bindgen]
+    pub fn set_metabolism(
+        &mut self,
+        basal_rate: f32,
+        movement_cost: f32,
+        exertion_multiplier: f32,
+        plant_efficiency: f32,
+        meat_efficiency: f32,
+    ) {
+        self.metabolism = Metabolism {
+            basal_rate,
+            movement_cost,
+            exertion_multiplier,
+            plant_efficiency,
+            meat_efficiency,
+        };
+    }
+
+    #[wasm_bindgen]
+    pub fn width(&self) -> f32 {
+        self.bounds.width
+    }
+
+    #[wasm_bindgen]
+    pub fn height(&self) -> f32 {
+        self.bounds.height
+    }
+
+    #[wasm_bindgen]
+    pub fn get_bounds(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.bounds).unwrap()
+    }
+
     #[wasm_bindgen]
     pub fn get_noise(&self, x: f64, y: f64) -> f64 {
         self.noise.get([x, y])
@@ -439,7 +954,12 @@ pub struct SerializedAgents {
     pub types: Vec<u8>,
     pub genotypes: Vec<Vec<f32>>,
     pub states: Vec<u8>,
-    pub vitals: Vec<(f32, f32)>, // Health, hunger
+    pub vitals: Vec<(f32, f32, f32)>, // Health, hunger, stamina
+    pub pregnancies: Vec<f32>,        // Gestation progress, 0 when not pregnant
+    pub ages: Vec<f32>,
+    pub sexes: Vec<u8>,      // 0 = male, 1 = female
+    pub infections: Vec<u8>, // Worst stage, 0 = susceptible, 1 = exposed, 2 = infectious, 3 = recovered
+    pub growth: Vec<f32>,    // Fraction of the adult size, 1 for plants and carcasses
 }
 impl SerializedAgents {
     pub fn new() -> SerializedAgents {
@@ -452,6 +972,11 @@ impl SerializedAgents {
             genotypes: Vec::new(),
             states: Vec::new(),
             vitals: Vec::new(),
+            pregnancies: Vec::new(),
+            ages: Vec::new(),
+            sexes: Vec::new(),
+            infections: Vec::new(),
+            growth: Vec::new(),
         }
     }
 }
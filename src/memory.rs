@@ -0,0 +1,162 @@
+use crate::{
+    agent::Agent,
+    behaviour::{normalize, Context},
+};
+
+// Sightings closer than this to a memory refresh it instead of taking a new slot
+const MERGE_DISTANCE: f32 = 20.;
+// Strength lost per tick, wolves move on so danger is forgotten faster than food
+const FOOD_DECAY: f32 = 0.002;
+const DANGER_DECAY: f32 = 0.01;
+// Fraction of the sight distance around the agent where it would notice remembered food
+const CHECK_RADIUS: f32 = 0.5;
+// Memories further than this have less pull on where the agent wanders
+const FALLOFF_DISTANCE: f32 = 100.;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MemoryKind {
+    Food,
+    Danger,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Memory {
+    pub kind: MemoryKind,
+    pub position: (f32, f32),
+    // 1 when just seen, forgotten once it fades to 0
+    pub strength: f32,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SpatialMemory {
+    pub memories: Vec<Memory>,
+}
+
+fn distance_squared(a: (f32, f32), b: (f32, f32)) -> f32 {
+    (a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)
+}
+
+impl SpatialMemory {
+    pub fn new() -> SpatialMemory {
+        SpatialMemory {
+            memories: Vec::new(),
+        }
+    }
+
+    fn decay(&mut self) {
+        for memory in self.memories.iter_mut() {
+            memory.strength -= match memory.kind {
+                MemoryKind::Food => FOOD_DECAY,
+                MemoryKind::Danger => DANGER_DECAY,
+            };
+        }
+        self.memories.retain(|memory| memory.strength > 0.);
+    }
+
+    // Refreshes a nearby memory of the same kind, or stores a new one in place of the faintest
+    fn remember(&mut self, kind: MemoryKind, position: (f32, f32), capacity: usize) {
+        if let Some(memory) = self.memories.iter_mut().find(|memory| {
+            memory.kind == kind
+                && distance_squared(memory.position, position) < MERGE_DISTANCE.powi(2)
+        }) {
+            memory.position = position;
+            memory.strength = 1.;
+            return;
+        }
+        let memory = Memory {
+            kind,
+            position,
+            strength: 1.,
+        };
+        if self.memories.len() < capacity {
+            self.memories.push(memory);
+            return;
+        }
+        let faintest = self
+            .memories
+            .iter_mut()
+            .min_by(|a, b| a.strength.partial_cmp(&b.strength).unwrap());
+        if let Some(faintest) = faintest {
+            if faintest.strength < 1. {
+                *faintest = memory;
+            }
+        }
+    }
+
+    // Direction the agent's memories pull it in, towards food the hungrier it is and away
+    // from danger
+    pub fn wander_direction(&self, position: (f32, f32), hunger: f32) -> Option<(f32, f32)> {
+        let appetite = (hunger / 100.).clamp(0., 1.);
+        let mut pull = (0., 0.);
+        for memory in self.memories.iter() {
+            let offset = (
+                memory.position.0 - position.0,
+                memory.position.1 - position.1,
+            );
+            let distance = (offset.0.powi(2) + offset.1.powi(2)).sqrt();
+            let direction = normalize(offset);
+            let weight = memory.strength / (1. + distance / FALLOFF_DISTANCE);
+            let weight = match memory.kind {
+                MemoryKind::Food => weight * appetite,
+                MemoryKind::Danger => -weight,
+            };
+            pull.0 += direction.0 * weight;
+            pull.1 += direction.1 * weight;
+        }
+        let pull = normalize(pull);
+        if pull == (0., 0.) {
+            None
+        } else {
+            Some(pull)
+        }
+    }
+}
+
+// Fades old memories and records the food and predators currently in sight
+pub fn update_memory(agent: &mut Agent, context: &Context) {
+    let capacity = context.genotype.memory_slots();
+    if capacity == 0 {
+        agent.memory.memories.clear();
+        return;
+    }
+    agent.memory.decay();
+
+    let mut food = Vec::new();
+    let mut danger = Vec::new();
+    for other in context.neighbours(agent) {
+        if other.dead {
+            continue;
+        }
+        let position = (other.position.0, other.position.1);
+        if context.species.fears(&agent.kind, &other.kind) {
+            danger.push(position);
+        } else if context
+            .species
+            .desirability(&agent.kind, &context.genotype, &other.kind)
+            > 0.
+        {
+            food.push(position);
+        }
+    }
+
+    // Food that should be in plain sight but isn't there any more has been eaten
+    let position = (agent.position.0, agent.position.1);
+    let check_radius =
+        context.genotype.sight_distance * context.clock.sight_multiplier() * CHECK_RADIUS;
+    agent.memory.memories.retain(|memory| {
+        memory.kind != MemoryKind::Food
+            || distance_squared(memory.position, position) > check_radius.powi(2)
+            || food
+                .iter()
+                .any(|f| distance_squared(*f, memory.position) < MERGE_DISTANCE.powi(2))
+    });
+
+    for position in danger {
+        agent
+            .memory
+            .remember(MemoryKind::Danger, position, capacity);
+    }
+    for position in food {
+        agent.memory.remember(MemoryKind::Food, position, capacity);
+    }
+}
@@ -0,0 +1,47 @@
+use crate::{agent::AgentType, genes::Genotype};
+
+const BASAL_RATE: f32 = 0.002;
+const MOVEMENT_COST: f32 = 0.0005;
+const EXERTION_MULTIPLIER: f32 = 2.;
+const PLANT_EFFICIENCY: f32 = 0.5;
+const MEAT_EFFICIENCY: f32 = 0.8;
+
+// Energy budget shared by all agents, hunger is the energy reserve
+#[derive(Clone, Copy, Debug)]
+pub struct Metabolism {
+    pub basal_rate: f32,
+    pub movement_cost: f32,
+    pub exertion_multiplier: f32,
+    pub plant_efficiency: f32,
+    pub meat_efficiency: f32,
+}
+
+impl Metabolism {
+    pub fn new() -> Metabolism {
+        Metabolism {
+            basal_rate: BASAL_RATE,
+            movement_cost: MOVEMENT_COST,
+            exertion_multiplier: EXERTION_MULTIPLIER,
+            plant_efficiency: PLANT_EFFICIENCY,
+            meat_efficiency: MEAT_EFFICIENCY,
+        }
+    }
+
+    // Energy burned over one tick, basal upkeep plus the kinetic cost of moving
+    pub fn energy_cost(&self, genotype: &Genotype, speed: f32, exerting: bool) -> f32 {
+        let basal = self.basal_rate * genotype.hunger_rate;
+        let mut movement = self.movement_cost * speed.powi(2) * genotype.mass;
+        if exerting {
+            movement *= self.exertion_multiplier;
+        }
+        basal + movement
+    }
+
+    // Fraction of eaten biomass turned into energy
+    pub fn food_efficiency(&self, food: &AgentType) -> f32 {
+        match food {
+            AgentType::Plant(_, _) => self.plant_efficiency,
+            _ => self.meat_efficiency,
+        }
+    }
+}
@@ -0,0 +1,725 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::{rngs::ThreadRng, seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    agent::Agent,
+    behaviour::{Behaviour, Context},
+    brain::{respond, sense, BRAIN_INPUTS, BRAIN_OUTPUTS},
+};
+
+// Node ids are fixed for the inputs, the bias and the outputs, hidden nodes come after
+const BIAS_NODE: usize = BRAIN_INPUTS;
+const FIRST_OUTPUT: usize = BRAIN_INPUTS + 1;
+const FIRST_HIDDEN: usize = FIRST_OUTPUT + BRAIN_OUTPUTS;
+
+const WEIGHT_RANGE: std::ops::Range<f32> = -1.0..1.0;
+const WEIGHT_MUTATION_CHANCE: f32 = 0.8;
+const WEIGHT_MUTATION_SIZE: f32 = 0.3;
+// Chance for a mutated weight to be replaced instead of nudged
+const WEIGHT_RESET_CHANCE: f32 = 0.1;
+const ADD_CONNECTION_CHANCE: f32 = 0.05;
+const ADD_NODE_CHANCE: f32 = 0.03;
+// Chance for a gene disabled in either parent to stay disabled in the child
+const INHERIT_DISABLED_CHANCE: f32 = 0.75;
+
+// Compatibility distance coefficients for excess genes, disjoint genes and weight differences
+const EXCESS_COEFFICIENT: f32 = 1.;
+const DISJOINT_COEFFICIENT: f32 = 1.;
+const WEIGHT_COEFFICIENT: f32 = 0.4;
+const COMPATIBILITY_THRESHOLD: f32 = 3.;
+// Simulation seconds between picks of new species representatives, there are no discrete
+// generations so this stands in for one
+const GENERATION_LENGTH: f32 = 30.;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum NodeKind {
+    Input,
+    Bias,
+    Output,
+    Hidden,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ConnectionGene {
+    pub innovation: usize,
+    pub from: usize,
+    pub to: usize,
+    pub weight: f32,
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct NeatGenome {
+    pub nodes: Vec<(usize, NodeKind)>,
+    // Kept sorted by innovation number
+    pub connections: Vec<ConnectionGene>,
+    pub species: usize,
+    // Hidden and output nodes in evaluation order, each with the enabled connections leading into
+    // it, rebuilt whenever the structure changes
+    order: Vec<(usize, Vec<usize>)>,
+}
+
+impl NeatGenome {
+    // Minimal topology, every input and the bias wired straight to every output
+    fn minimal(thread_rng: &mut ThreadRng) -> NeatGenome {
+        let mut nodes = Vec::new();
+        for id in 0..BRAIN_INPUTS {
+            nodes.push((id, NodeKind::Input));
+        }
+        nodes.push((BIAS_NODE, NodeKind::Bias));
+        for id in FIRST_OUTPUT..FIRST_HIDDEN {
+            nodes.push((id, NodeKind::Output));
+        }
+
+        let mut connections = Vec::new();
+        for from in 0..FIRST_OUTPUT {
+            for to in FIRST_OUTPUT..FIRST_HIDDEN {
+                connections.push(ConnectionGene {
+                    innovation: from * BRAIN_OUTPUTS + to - FIRST_OUTPUT,
+                    from,
+                    to,
+                    weight: thread_rng.gen_range(WEIGHT_RANGE),
+                    enabled: true,
+                });
+            }
+        }
+
+        let mut genome = NeatGenome {
+            nodes,
+            connections,
+            species: 0,
+            order: Vec::new(),
+        };
+        genome.build_order();
+        genome
+    }
+
+    fn kind_of(&self, node: usize) -> Option<NodeKind> {
+        self.nodes
+            .iter()
+            .find(|(id, _)| *id == node)
+            .map(|(_, kind)| *kind)
+    }
+
+    pub fn hidden_nodes(&self) -> usize {
+        self.nodes
+            .iter()
+            .filter(|(_, kind)| *kind == NodeKind::Hidden)
+            .count()
+    }
+
+    pub fn enabled_connections(&self) -> usize {
+        self.connections.iter().filter(|c| c.enabled).count()
+    }
+
+    // Whether a path already leads from one node to the other
+    fn reaches(&self, from: usize, to: usize) -> bool {
+        let mut stack = vec![from];
+        let mut visited = HashSet::new();
+        while let Some(node) = stack.pop() {
+            if node == to {
+                return true;
+            }
+            if !visited.insert(node) {
+                continue;
+            }
+            for connection in self.connections.iter().filter(|c| c.from == node) {
+                stack.push(connection.to);
+            }
+        }
+        false
+    }
+
+    // Sorts the hidden and output nodes so each comes after every node feeding into it,
+    // connections never form cycles
+    fn build_order(&mut self) {
+        let mut incoming: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (index, connection) in self.connections.iter().enumerate() {
+            if connection.enabled {
+                incoming.entry(connection.to).or_default().push(index);
+            }
+        }
+        let mut remaining: HashMap<usize, usize> = incoming
+            .iter()
+            .map(|(node, links)| (*node, links.len()))
+            .collect();
+
+        // Inputs, the bias and nodes nothing feeds into can be evaluated straight away
+        let mut ready: Vec<usize> = (0..FIRST_OUTPUT).collect();
+        for (id, _) in self.nodes.iter() {
+            if *id >= FIRST_OUTPUT && !incoming.contains_key(id) {
+                ready.push(*id);
+            }
+        }
+
+        let mut order = Vec::new();
+        while let Some(node) = ready.pop() {
+            if node >= FIRST_OUTPUT {
+                order.push((node, incoming.remove(&node).unwrap_or_default()));
+            }
+            for connection in self.connections.iter() {
+                if !connection.enabled || connection.from != node {
+                    continue;
+                }
+                let count = remaining.get_mut(&connection.to).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    ready.push(connection.to);
+                }
+            }
+        }
+        self.order = order;
+    }
+
+    // Evaluates the network in the cached topological order, all activations are tanh
+    pub fn activate(&self, inputs: &[f32; BRAIN_INPUTS]) -> [f32; BRAIN_OUTPUTS] {
+        let size = self.nodes.iter().map(|(id, _)| id + 1).max().unwrap_or(0);
+        let mut values = vec![0.; size.max(FIRST_HIDDEN)];
+        values[..BRAIN_INPUTS].copy_from_slice(inputs);
+        values[BIAS_NODE] = 1.;
+
+        for (node, incoming) in self.order.iter() {
+            let sum: f32 = incoming
+                .iter()
+                .map(|index| {
+                    let connection = &self.connections[*index];
+                    connection.weight * values[connection.from]
+                })
+                .sum();
+            values[*node] = sum.tanh();
+        }
+
+        let mut outputs = [0.; BRAIN_OUTPUTS];
+        outputs.copy_from_slice(&values[FIRST_OUTPUT..FIRST_HIDDEN]);
+        outputs
+    }
+
+    // Lines up genes by innovation number, matching genes come from either parent and
+    // disjoint or excess genes from the fitter one
+    pub fn crossover(
+        &self,
+        other: &NeatGenome,
+        self_is_fitter: bool,
+        thread_rng: &mut ThreadRng,
+    ) -> NeatGenome {
+        let (fitter, weaker) = if self_is_fitter {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let weaker_genes: HashMap<usize, &ConnectionGene> = weaker
+            .connections
+            .iter()
+            .map(|c| (c.innovation, c))
+            .collect();
+
+        let mut connections = Vec::new();
+        for gene in fitter.connections.iter() {
+            let mut child_gene = *gene;
+            if let Some(matching) = weaker_genes.get(&gene.innovation) {
+                if thread_rng.gen_bool(0.5) {
+                    child_gene.weight = matching.weight;
+                }
+                if !gene.enabled || !matching.enabled {
+                    child_gene.enabled = thread_rng.gen::<f32>() >= INHERIT_DISABLED_CHANCE;
+                }
+            }
+            connections.push(child_gene);
+        }
+
+        let mut child = NeatGenome {
+            nodes: fitter.nodes.clone(),
+            connections,
+            species: fitter.species,
+            order: Vec::new(),
+        };
+        child.build_order();
+        child
+    }
+
+    pub fn mutate(&mut self, innovations: &mut Innovations, thread_rng: &mut ThreadRng) {
+        for connection in self.connections.iter_mut() {
+            if thread_rng.gen::<f32>() < WEIGHT_MUTATION_CHANCE {
+                if thread_rng.gen::<f32>() < WEIGHT_RESET_CHANCE {
+                    connection.weight = thread_rng.gen_range(WEIGHT_RANGE);
+                } else {
+                    connection.weight += (thread_rng.gen::<f32>() * 2. - 1.) * WEIGHT_MUTATION_SIZE;
+                }
+            }
+        }
+        if thread_rng.gen::<f32>() < ADD_CONNECTION_CHANCE {
+            self.add_connection(innovations, thread_rng);
+        }
+        if thread_rng.gen::<f32>() < ADD_NODE_CHANCE {
+            self.add_node(innovations, thread_rng);
+        }
+        self.build_order();
+    }
+
+    // Wires two unconnected nodes together, skipping links that would form a cycle
+    fn add_connection(&mut self, innovations: &mut Innovations, thread_rng: &mut ThreadRng) {
+        let sources: Vec<usize> = self
+            .nodes
+            .iter()
+            .filter(|(_, kind)| *kind != NodeKind::Output)
+            .map(|(id, _)| *id)
+            .collect();
+        let targets: Vec<usize> = self
+            .nodes
+            .iter()
+            .filter(|(_, kind)| *kind == NodeKind::Output || *kind == NodeKind::Hidden)
+            .map(|(id, _)| *id)
+            .collect();
+
+        let from = *sources.choose(thread_rng).unwrap();
+        let to = *targets.choose(thread_rng).unwrap();
+        if from == to
+            || self
+                .connections
+                .iter()
+                .any(|c| c.from == from && c.to == to)
+            || self.reaches(to, from)
+        {
+            return;
+        }
+
+        self.connections.push(ConnectionGene {
+            innovation: innovations.connection(from, to),
+            from,
+            to,
+            weight: thread_rng.gen_range(WEIGHT_RANGE),
+            enabled: true,
+        });
+        self.connections.sort_by_key(|c| c.innovation);
+    }
+
+    // Splits an enabled connection in two with a new hidden node in between
+    fn add_node(&mut self, innovations: &mut Innovations, thread_rng: &mut ThreadRng) {
+        let enabled: Vec<usize> = (0..self.connections.len())
+            .filter(|i| self.connections[*i].enabled)
+            .collect();
+        let index = match enabled.choose(thread_rng) {
+            Some(index) => *index,
+            None => return,
+        };
+        let old = self.connections[index];
+        let node = innovations.split(old.innovation);
+        // The same split can only happen once per genome
+        if self.kind_of(node).is_some() {
+            return;
+        }
+        self.connections[index].enabled = false;
+        self.nodes.push((node, NodeKind::Hidden));
+
+        // The incoming link starts at 1 so the network behaves the same as before the split
+        self.connections.push(ConnectionGene {
+            innovation: innovations.connection(old.from, node),
+            from: old.from,
+            to: node,
+            weight: 1.,
+            enabled: true,
+        });
+        self.connections.push(ConnectionGene {
+            innovation: innovations.connection(node, old.to),
+            from: node,
+            to: old.to,
+            weight: old.weight,
+            enabled: true,
+        });
+        self.connections.sort_by_key(|c| c.innovation);
+    }
+
+    pub fn compatibility(&self, other: &NeatGenome) -> f32 {
+        let self_max = self.connections.last().map_or(0, |c| c.innovation);
+        let other_max = other.connections.last().map_or(0, |c| c.innovation);
+        let other_genes: HashMap<usize, f32> = other
+            .connections
+            .iter()
+            .map(|c| (c.innovation, c.weight))
+            .collect();
+
+        let mut matching = 0;
+        let mut weight_difference = 0.;
+        let mut disjoint = 0;
+        let mut excess = 0;
+        for gene in self.connections.iter() {
+            match other_genes.get(&gene.innovation) {
+                Some(weight) => {
+                    matching += 1;
+                    weight_difference += (gene.weight - weight).abs();
+                }
+                None if gene.innovation > other_max => excess += 1,
+                None => disjoint += 1,
+            }
+        }
+        for gene in other.connections.iter() {
+            if self
+                .connections
+                .binary_search_by_key(&gene.innovation, |c| c.innovation)
+                .is_err()
+            {
+                if gene.innovation > self_max {
+                    excess += 1;
+                } else {
+                    disjoint += 1;
+                }
+            }
+        }
+
+        let size = self.connections.len().max(other.connections.len()).max(1) as f32;
+        let average_weight = if matching > 0 {
+            weight_difference / matching as f32
+        } else {
+            0.
+        };
+        EXCESS_COEFFICIENT * excess as f32 / size
+            + DISJOINT_COEFFICIENT * disjoint as f32 / size
+            + WEIGHT_COEFFICIENT * average_weight
+    }
+}
+
+// Historical markings, the same structural change always gets the same number
+pub struct Innovations {
+    next_innovation: usize,
+    next_node: usize,
+    connections: HashMap<(usize, usize), usize>,
+    splits: HashMap<usize, usize>,
+}
+
+impl Innovations {
+    pub fn new() -> Innovations {
+        Innovations {
+            // The minimal topology uses the first innovation numbers
+            next_innovation: FIRST_OUTPUT * BRAIN_OUTPUTS,
+            next_node: FIRST_HIDDEN,
+            connections: HashMap::new(),
+            splits: HashMap::new(),
+        }
+    }
+
+    pub fn connection(&mut self, from: usize, to: usize) -> usize {
+        if from < FIRST_OUTPUT && (FIRST_OUTPUT..FIRST_HIDDEN).contains(&to) {
+            return from * BRAIN_OUTPUTS + to - FIRST_OUTPUT;
+        }
+        let next = &mut self.next_innovation;
+        *self.connections.entry((from, to)).or_insert_with(|| {
+            *next += 1;
+            *next - 1
+        })
+    }
+
+    pub fn split(&mut self, innovation: usize) -> usize {
+        let next = &mut self.next_node;
+        *self.splits.entry(innovation).or_insert_with(|| {
+            *next += 1;
+            *next - 1
+        })
+    }
+}
+
+pub struct Species {
+    pub id: usize,
+    pub representative: NeatGenome,
+}
+
+// Innovation history and species shared by every evolving brain in the world
+pub struct Neat {
+    pub innovations: Innovations,
+    pub species: Vec<Species>,
+    next_species: usize,
+    // When the representatives were last picked
+    last_generation: f32,
+}
+
+impl Neat {
+    pub fn new() -> Neat {
+        Neat {
+            innovations: Innovations::new(),
+            species: Vec::new(),
+            next_species: 0,
+            last_generation: 0.,
+        }
+    }
+
+    pub fn seed_genome(&mut self, thread_rng: &mut ThreadRng) -> NeatGenome {
+        let mut genome = NeatGenome::minimal(thread_rng);
+        self.speciate(&mut genome);
+        genome
+    }
+
+    pub fn offspring(
+        &mut self,
+        mother: &NeatGenome,
+        father: &NeatGenome,
+        mother_is_fitter: bool,
+        thread_rng: &mut ThreadRng,
+    ) -> NeatGenome {
+        let mut genome = mother.crossover(father, mother_is_fitter, thread_rng);
+        genome.mutate(&mut self.innovations, thread_rng);
+        self.speciate(&mut genome);
+        genome
+    }
+
+    // Joins the first species whose representative is close enough, or founds a new one
+    pub fn speciate(&mut self, genome: &mut NeatGenome) {
+        for species in self.species.iter() {
+            if genome.compatibility(&species.representative) < COMPATIBILITY_THRESHOLD {
+                genome.species = species.id;
+                return;
+            }
+        }
+        genome.species = self.next_species;
+        self.species.push(Species {
+            id: self.next_species,
+            representative: genome.clone(),
+        });
+        self.next_species += 1;
+    }
+
+    // Forgets species without living members, and once a generation picks a random member of
+    // each species as the representative new genomes are compared against
+    pub fn update(&mut self, agents: &HashMap<Uuid, Agent>, time: f32, thread_rng: &mut ThreadRng) {
+        let mut members: HashMap<usize, Vec<&NeatGenome>> = HashMap::new();
+        for genome in agents.values().filter_map(|agent| agent.neat.as_ref()) {
+            members.entry(genome.species).or_default().push(genome);
+        }
+        self.species
+            .retain(|species| members.contains_key(&species.id));
+
+        if time - self.last_generation < GENERATION_LENGTH {
+            return;
+        }
+        self.last_generation = time;
+        for species in self.species.iter_mut() {
+            if let Some(genome) = members[&species.id].choose(thread_rng) {
+                species.representative = (*genome).clone();
+            }
+        }
+    }
+
+    // Drops every species, used when the evolving brains are switched off
+    pub fn clear(&mut self) {
+        self.species.clear();
+    }
+
+    pub fn statistics(&self, agents: &HashMap<Uuid, Agent>) -> Vec<SerializedSpecies> {
+        let mut result: Vec<SerializedSpecies> = self
+            .species
+            .iter()
+            .map(|species| SerializedSpecies {
+                id: species.id,
+                members: 0,
+                hidden_nodes: 0.,
+                connections: 0.,
+            })
+            .collect();
+        for genome in agents.values().filter_map(|agent| agent.neat.as_ref()) {
+            if let Some(species) = result.iter_mut().find(|s| s.id == genome.species) {
+                species.members += 1;
+                species.hidden_nodes += genome.hidden_nodes() as f32;
+                species.connections += genome.enabled_connections() as f32;
+            }
+        }
+        for species in result.iter_mut() {
+            if species.members > 0 {
+                species.hidden_nodes /= species.members as f32;
+                species.connections /= species.members as f32;
+            }
+        }
+        result
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SerializedSpecies {
+    pub id: usize,
+    pub members: usize,
+    // Averages over the members
+    pub hidden_nodes: f32,
+    pub connections: f32,
+}
+
+// Lets the evolved topology drive the agent, agents without a genome stand still
+pub struct Evolve;
+impl Behaviour for Evolve {
+    fn name(&self) -> &'static str {
+        "Evolve"
+    }
+
+    fn is_running(&self, _agent: &Agent, _context: &Context) -> bool {
+        true
+    }
+
+    fn score(&self, _agent: &Agent, _context: &Context) -> f32 {
+        1.
+    }
+
+    fn act(
+        &self,
+        agent: &mut Agent,
+        context: &Context,
+        _thread_rng: &mut ThreadRng,
+        modified_agents: &mut HashMap<Uuid, Agent>,
+    ) {
+        let senses = sense(agent, context);
+        let outputs = match &agent.neat {
+            Some(genome) => genome.activate(&senses.inputs),
+            None => return,
+        };
+        respond(agent, context, &senses, &outputs, modified_agents);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Genome with the fixed nodes, the given hidden nodes and (innovation, from, to, weight) genes
+    fn genome(genes: &[(usize, usize, usize, f32)], hidden: &[usize]) -> NeatGenome {
+        let mut nodes: Vec<(usize, NodeKind)> =
+            (0..BRAIN_INPUTS).map(|id| (id, NodeKind::Input)).collect();
+        nodes.push((BIAS_NODE, NodeKind::Bias));
+        nodes.extend((FIRST_OUTPUT..FIRST_HIDDEN).map(|id| (id, NodeKind::Output)));
+        nodes.extend(hidden.iter().map(|id| (*id, NodeKind::Hidden)));
+        let mut genome = NeatGenome {
+            nodes,
+            connections: genes
+                .iter()
+                .map(|(innovation, from, to, weight)| ConnectionGene {
+                    innovation: *innovation,
+                    from: *from,
+                    to: *to,
+                    weight: *weight,
+                    enabled: true,
+                })
+                .collect(),
+            species: 0,
+            order: Vec::new(),
+        };
+        genome.build_order();
+        genome
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn activate_tiny_network() {
+        let hidden = FIRST_HIDDEN;
+        // The hidden node's outgoing link is listed before the link feeding it
+        let mut network = genome(
+            &[
+                (0, hidden, FIRST_OUTPUT + 2, 0.5),
+                (1, 0, FIRST_OUTPUT, 0.5),
+                (2, BIAS_NODE, FIRST_OUTPUT + 1, 1.),
+                (3, 1, hidden, 2.),
+                (4, 2, FIRST_OUTPUT + 3, 1.),
+            ],
+            &[hidden],
+        );
+        network.connections[4].enabled = false;
+        network.build_order();
+
+        let mut inputs = [0.; BRAIN_INPUTS];
+        inputs[0] = 0.5;
+        inputs[1] = 1.;
+        inputs[2] = 1.;
+        let outputs = network.activate(&inputs);
+        assert!(close(outputs[0], (0.5f32 * 0.5).tanh()));
+        assert!(close(outputs[1], 1f32.tanh()));
+        assert!(close(outputs[2], (0.5 * 2f32.tanh()).tanh()));
+        // Disabled links don't count
+        assert!(close(outputs[3], 0.));
+    }
+
+    #[test]
+    fn activate_minimal_network() {
+        let mut rng = rand::thread_rng();
+        let network = NeatGenome::minimal(&mut rng);
+        let inputs = [0.; BRAIN_INPUTS];
+        let outputs = network.activate(&inputs);
+        // With all inputs at 0 only the bias drives the outputs
+        for (i, output) in outputs.iter().enumerate() {
+            let bias = network
+                .connections
+                .iter()
+                .find(|c| c.from == BIAS_NODE && c.to == FIRST_OUTPUT + i)
+                .unwrap();
+            assert!(close(*output, bias.weight.tanh()));
+        }
+    }
+
+    #[test]
+    fn compatibility_counts_disjoint_excess_and_weights() {
+        let a = genome(
+            &[
+                (0, 0, FIRST_OUTPUT, 1.),
+                (1, 1, FIRST_OUTPUT, 0.),
+                (2, 2, FIRST_OUTPUT, 0.),
+            ],
+            &[],
+        );
+        let b = genome(
+            &[
+                (0, 0, FIRST_OUTPUT, 0.),
+                (1, 1, FIRST_OUTPUT, 0.),
+                (3, 3, FIRST_OUTPUT, 0.),
+                (4, 4, FIRST_OUTPUT, 0.),
+            ],
+            &[],
+        );
+        assert!(close(a.compatibility(&a), 0.));
+        // One disjoint gene, two excess genes out of four, and an average weight difference of 0.5
+        let expected = DISJOINT_COEFFICIENT * 1. / 4.
+            + EXCESS_COEFFICIENT * 2. / 4.
+            + WEIGHT_COEFFICIENT * 0.5;
+        assert!(close(a.compatibility(&b), expected));
+        assert!(close(b.compatibility(&a), expected));
+    }
+
+    #[test]
+    fn crossover_takes_structure_from_the_fitter_parent() {
+        let mut rng = rand::thread_rng();
+        let fitter = genome(
+            &[
+                (0, 0, FIRST_OUTPUT, 1.),
+                (1, 1, FIRST_OUTPUT, 1.),
+                (2, 2, FIRST_OUTPUT, 1.),
+            ],
+            &[],
+        );
+        let weaker = genome(
+            &[
+                (0, 0, FIRST_OUTPUT, -1.),
+                (1, 1, FIRST_OUTPUT, -1.),
+                (3, 3, FIRST_OUTPUT, -1.),
+            ],
+            &[],
+        );
+
+        let mut from_weaker = 0;
+        for _ in 0..100 {
+            let child = fitter.crossover(&weaker, true, &mut rng);
+            let innovations: Vec<usize> = child.connections.iter().map(|c| c.innovation).collect();
+            assert_eq!(innovations, vec![0, 1, 2]);
+            // Disjoint genes only come from the fitter parent, matching ones from either
+            assert!(close(child.connections[2].weight, 1.));
+            for gene in child.connections[..2].iter() {
+                assert!(close(gene.weight.abs(), 1.));
+                if gene.weight < 0. {
+                    from_weaker += 1;
+                }
+            }
+        }
+        assert!(from_weaker > 0 && from_weaker < 200);
+
+        // Swapping who is fitter swaps whose structure is kept
+        let child = weaker.crossover(&fitter, true, &mut rng);
+        let innovations: Vec<usize> = child.connections.iter().map(|c| c.innovation).collect();
+        assert_eq!(innovations, vec![0, 1, 3]);
+    }
+}
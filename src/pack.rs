@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    agent::{Agent, State},
+    behaviour::{distance_squared, normalize, Context},
+    species::SpeciesRegistry,
+};
+
+// Packmates further apart than this lose track of each other
+const PACK_RADIUS: f32 = 120.;
+// How long a wolf stays in its pack without seeing any packmate
+const PACK_TIMEOUT: f32 = 20.;
+const MAX_PACK_SIZE: usize = 8;
+// Average sociability two wolves need before they team up
+const JOIN_THRESHOLD: f32 = 0.5;
+// Distance from the prey at which the pack spreads out before closing in
+const ENCIRCLE_RADIUS: f32 = 25.;
+
+// Living members of the agent's pack within sight
+pub fn packmates<'a>(agent: &Agent, context: &Context<'a>) -> Vec<&'a Agent> {
+    let pack = match agent.pack {
+        Some(pack) => pack,
+        None => return Vec::new(),
+    };
+    context
+        .neighbours(agent)
+        .into_iter()
+        .filter(|other| {
+            !other.dead
+                && other.kind.is_animal()
+                && other.pack == Some(pack)
+                && distance_squared(agent, other) < PACK_RADIUS.powi(2)
+        })
+        .collect()
+}
+
+fn visible_pack_size(pack: Uuid, context: &Context) -> usize {
+    context
+        .nearby_agents
+        .iter()
+        .filter_map(|a| context.agents.get(&a.0))
+        .filter(|other| !other.dead && other.kind.is_animal() && other.pack == Some(pack))
+        .count()
+}
+
+// Keeps track of the pack, wolves without visible packmates team up with the closest
+// sociable wolf, the smallest pack id wins so both sides agree on the pack
+pub fn update_pack(agent: &mut Agent, context: &Context) {
+    if !packmates(agent, context).is_empty() {
+        agent.pack_seen = context.time;
+        return;
+    }
+    if agent.pack.is_some() && context.time - agent.pack_seen > PACK_TIMEOUT {
+        agent.pack = None;
+    }
+
+    let sociability = context.genotype.sociability;
+    let mut closest_distance = f32::MAX;
+    let mut closest = None;
+    for other in context.neighbours(agent) {
+        if other.dead || other.kind.species() != agent.kind.species() {
+            continue;
+        }
+        let other_sociability = match other.kind.genotype() {
+            Some(genotype) => genotype.sociability,
+            None => continue,
+        };
+        if (sociability + other_sociability) / 2. < JOIN_THRESHOLD {
+            continue;
+        }
+        if let Some(pack) = other.pack {
+            if visible_pack_size(pack, context) >= MAX_PACK_SIZE {
+                continue;
+            }
+        }
+        let distance = distance_squared(agent, other);
+        if distance < closest_distance {
+            closest_distance = distance;
+            closest = Some(other);
+        }
+    }
+
+    if let Some(other) = closest {
+        let candidate = other.pack.unwrap_or(other.id);
+        agent.pack = Some(match agent.pack {
+            Some(own) => own.min(candidate),
+            None => agent.id.min(candidate),
+        });
+        agent.pack_seen = context.time;
+    }
+}
+
+// Prey or kill a packmate is already going after, so the pack works on a single target
+pub fn shared_target<'a>(agent: &Agent, context: &Context<'a>) -> Option<&'a Agent> {
+    for packmate in packmates(agent, context) {
+        if let State::Hunting(target) = packmate.state {
+            if let Some(food) = context.get_nearby(target) {
+                if !food.dead
+                    && !food.kind.is_plant()
+                    && context.species.eats(&agent.kind, &food.kind)
+                {
+                    return Some(food);
+                }
+            }
+        }
+    }
+    None
+}
+
+// Packmates that were chasing the prey when it went down split the killing bite evenly with
+// the killer
+pub fn share_kill(
+    agent: &mut Agent,
+    prey: &Agent,
+    energy: f32,
+    context: &Context,
+    modified_agents: &mut HashMap<Uuid, Agent>,
+) {
+    let hunters: Vec<&Agent> = packmates(agent, context)
+        .into_iter()
+        .filter(|packmate| matches!(packmate.state, State::Hunting(target) if target == prey.id))
+        .collect();
+    if hunters.is_empty() {
+        return;
+    }
+    let share = energy / (hunters.len() + 1) as f32;
+    agent.hunger -= energy - share;
+    for hunter in hunters {
+        let mut hunter = modified_agents
+            .get(&hunter.id)
+            .cloned()
+            .unwrap_or_else(|| hunter.clone());
+        hunter.hunger = (hunter.hunger + share).min(100.);
+        hunter.assists += 1;
+        modified_agents.insert(hunter.id, hunter);
+    }
+}
+
+// Where to run so the pack closes in on live prey from every side at once
+pub fn approach_point(agent: &Agent, context: &Context, prey: &Agent) -> (f32, f32) {
+    let prey_position = (prey.position.0, prey.position.1);
+    if !prey.kind.is_animal() || distance_squared(agent, prey) < ENCIRCLE_RADIUS.powi(2) {
+        return prey_position;
+    }
+
+    // Head for the side of the prey opposite to where the rest of the pack already is
+    let mut others = (0., 0.);
+    for packmate in packmates(agent, context) {
+        if let State::Hunting(target) = packmate.state {
+            if target == prey.id {
+                let direction = normalize((
+                    packmate.position.0 - prey.position.0,
+                    packmate.position.1 - prey.position.1,
+                ));
+                others.0 += direction.0;
+                others.1 += direction.1;
+            }
+        }
+    }
+    let side = normalize((-others.0, -others.1));
+    if side == (0., 0.) {
+        return prey_position;
+    }
+    let flank = (
+        prey.position.0 + side.0 * ENCIRCLE_RADIUS,
+        prey.position.1 + side.1 * ENCIRCLE_RADIUS,
+    );
+
+    // Charge once in position
+    if (flank.0 - agent.position.0).powi(2) + (flank.1 - agent.position.1).powi(2)
+        < ENCIRCLE_RADIUS.powi(2)
+    {
+        return prey_position;
+    }
+    flank
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SerializedPacks {
+    pub packs: usize,
+    pub pack_wolves: usize,
+    pub lone_wolves: usize,
+    // Kills made by the living wolves, split by whether they hunt in a pack
+    pub pack_kills: u32,
+    pub lone_kills: u32,
+    // Kills pack wolves shared in without landing the final bite
+    pub pack_assists: u32,
+}
+
+pub fn statistics(agents: &HashMap<Uuid, Agent>, species: &SpeciesRegistry) -> SerializedPacks {
+    let mut sizes: HashMap<Uuid, usize> = HashMap::new();
+    let mut result = SerializedPacks {
+        packs: 0,
+        pack_wolves: 0,
+        lone_wolves: 0,
+        pack_kills: 0,
+        lone_kills: 0,
+        pack_assists: 0,
+    };
+    for agent in agents.values() {
+        if agent.dead || !species.hunts_in_packs(&agent.kind) {
+            continue;
+        }
+        if let Some(pack) = agent.pack {
+            *sizes.entry(pack).or_insert(0) += 1;
+        }
+    }
+    for agent in agents.values() {
+        if agent.dead || !species.hunts_in_packs(&agent.kind) {
+            continue;
+        }
+        // A pack of one is still a lone wolf
+        let in_pack = agent
+            .pack
+            .is_some_and(|pack| sizes.get(&pack).copied().unwrap_or(0) > 1);
+        if in_pack {
+            result.pack_wolves += 1;
+            result.pack_kills += agent.kills;
+            result.pack_assists += agent.assists;
+        } else {
+            result.lone_wolves += 1;
+            result.lone_kills += agent.kills;
+        }
+    }
+    result.packs = sizes.values().filter(|size| **size > 1).count();
+    result
+}
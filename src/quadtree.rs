@@ -2,7 +2,7 @@ use std::{collections::HashMap, thread::current};
 use uuid::Uuid;
 use wasm_bindgen::prelude::*;
 
-use crate::{log, Agent, MAX_CHILDREN, MAX_LEVELS};
+use crate::{log, Agent, Bounds, MAX_CHILDREN, MAX_LEVELS};
 
 use serde::{Deserialize, Serialize};
 
@@ -23,12 +23,12 @@ pub struct QuadTree {
 }
 
 impl QuadTree {
-    pub fn new(size: f32) -> QuadTree {
+    pub fn new(position: (f32, f32), size: f32) -> QuadTree {
         QuadTree {
             children: Vec::new(),
             child_nodes: Vec::with_capacity(4),
             is_leaf: true,
-            position: (0., 0.),
+            position,
             size,
             level: 0,
             index: 0,
@@ -37,6 +37,11 @@ impl QuadTree {
         }
     }
 
+    // Builds a square root node large enough to cover a rectangular area
+    pub fn covering(bounds: &Bounds) -> QuadTree {
+        QuadTree::new((bounds.x, bounds.y), bounds.width.max(bounds.height))
+    }
+
     pub fn subdivide(&mut self, agent_list: &HashMap<Uuid, Agent>) {
         /* log(&format!("Subdividing at level {}", self.level)); */
         for i in 0..4 {
@@ -177,12 +182,10 @@ impl QuadTree {
         let closest_point = (
             position
                 .0
-                .max(self.position.0)
-                .min(self.position.0 + self.size),
+                .clamp(self.position.0, self.position.0 + self.size),
             position
                 .1
-                .max(self.position.1)
-                .min(self.position.1 + self.size),
+                .clamp(self.position.1, self.position.1 + self.size),
         );
         let distance =
             (position.0 - closest_point.0).powi(2) + (position.1 - closest_point.1).powi(2);
@@ -14,7 +14,17 @@ import { OrbitControls } from 'three/examples/jsm/controls/OrbitControls.js';
 import { EXRLoader } from 'three/examples/jsm/loaders/EXRLoader.js';
 
 
-const MAX_GRASS = 1024;
+// Instances allocated up front for species with few or no individuals at the start
+const MIN_INSTANCES = 64;
+const MAX_CARCASSES = 256;
+
+// Models of the built-in species, other species get a plain shape picked from their role
+const SPECIES_MODELS = {
+    Wolf: { path: "./rsc/models/wolf.glb", scale: 2 },
+    Sheep: { path: "./rsc/models/sheep.glb", scale: 1.6 },
+    Grass: { path: "./rsc/models/grass.glb", scale: 4 },
+};
+const SPECIES_PALETTE = [0x8a6f4d, 0xc9b38f, 0x4d5a8a, 0x8a4d6f, 0x6f8a4d, 0x5e5e5e];
 
 
 THREE.MapControls = function (object, domElement) {
@@ -39,14 +49,17 @@ log(Math.lerp(0, 1, .5));
 
 
 export class Renderer {
-    constructor(sheepNumber, wolfNumber, size, world) {
-        log(`Renderer started with ${wolfNumber} wolves and ${sheepNumber} sheep.`)
+    constructor(width, height, origin, world) {
+        log(`Renderer started on a ${width}x${height} world.`)
         this.texLoader = new THREE.TextureLoader();
         this.load_models();
         this.start = performance.now()
-        this.size = size;
-        this.sheepNumber = sheepNumber;
-        this.wolfNumber = wolfNumber;
+        this.width = width;
+        this.height = height;
+        // World coordinates of the middle of the ground plane, which sits at the scene origin
+        this.center = [origin[0] + width / 2, origin[1] + height / 2];
+        // Largest extent, used to place the camera and the sun
+        this.size = Math.max(width, height);
 
         this.world = world;
         this.custom_noise_scale = 0.01
@@ -78,7 +91,7 @@ export class Renderer {
         this.mouse = { x: 0, y: 0 }
 
         this.ground = new THREE.Mesh(
-            new THREE.PlaneGeometry(this.size, this.size, Math.sqrt(this.size), Math.sqrt(this.size)),
+            new THREE.PlaneGeometry(this.width, this.height, Math.sqrt(this.width), Math.sqrt(this.height)),
             new THREE.MeshPhysicalMaterial({
                 color: 0x002611,
                 roughness: 1,
@@ -107,187 +120,98 @@ export class Renderer {
     update_agents(agents) {
         if (!this.done_loading) return;
 
-        /* for(let i = 0; i < this.wolfNumber; i++) {
-            let m = new THREE.Matrix4();
-            m.makeTranslation(i * 10, 10, 0)
-            this.wolves.setMatrixAt(i, m)
-        }
-        this.wolves.instanceMatrix.needsUpdate = true
-
-        return */
-
         /* let updated_instances = 0 */
 
         /* log(`Updating ${agents.positions.length} agents' matrices.`); */
 
-        let updated_wolf = 0;
-        let updated_sheep = 0;
-        let updated_grass = 0;
+        for (const entry of this.species_meshes) {
+            if (entry) entry.count = 0;
+        }
 
         const m = new THREE.Matrix4();
 
-        // TODO: This is bad, we're iterating over all agents 
-        // FIX: Track which agent we're updating into and use that as an index
-
         // TODO: Have a bouncy animation for moving agents, like a sine(time + agents.seeds[i]) that's multiplied by agents.accelerations[i][0] and agents.accelerations[i][1]
         for (let i = 0; i < agents.positions.length; i++) {
-            const dead = agents.states[i] == 4;
-            const seed = parseInt(parseInt(agents.ids[i].replace(/-/g, ""), 16).toString(36), 36);
-            /* log(seed) */
-            /* if(dead) log("dead") */
-            let prevrot, scaled_acceleration;
-            if (agents.types[i] != 2) {
-                prevrot = Math.atan2(agents.accelerations[i][0], agents.accelerations[i][1]);
-                scaled_acceleration = Math.sqrt(agents.accelerations[i][0] ** 2 + agents.accelerations[i][1] ** 2) / agents.genotypes[5];
+            let entry = this.species_meshes[agents.types[i]];
+            if (!entry) {
+                // A species was added since the meshes were made
+                this.refresh_species();
+                entry = this.species_meshes[agents.types[i]];
+                if (!entry) continue;
+            }
+            if (entry.count >= entry.capacity) {
+                this.grow_species_mesh(entry);
             }
-            switch (agents.types[i]) {
-                case 0:
+
+            const position = new THREE.Vector3(
+                agents.positions[i][0] - this.center[0],
+                agents.positions[i][2] * 12.5,
+                agents.positions[i][1] - this.center[1]
+            );
+            switch (entry.role) {
+                case "Animal": {
+                    const dead = agents.states[i] == 4;
+                    const heading = Math.atan2(agents.accelerations[i][0], agents.accelerations[i][1]);
+                    const size = agents.genotypes[i][0] * agents.growth[i] / 10;
                     m.compose(
-                        new THREE.Vector3(
-                            agents.positions[i][0] - this.size / 2,
-                            agents.positions[i][2] * 12.5,
-                            agents.positions[i][1] - this.size / 2
-                        ),
-                        new THREE.Quaternion().setFromEuler(
-                            new THREE.Euler(
-                                0,
-                                Math.lerp(prevrot, Math.atan2(agents.accelerations[i][0], agents.accelerations[i][1]), .1),
-                                dead ? 1.4 : 0
-                            )
-                        ),
-                        new THREE.Vector3(agents.genotypes[i][0] / 10, agents.genotypes[i][0] / 10, agents.genotypes[i][0] / 10)
+                        position,
+                        new THREE.Quaternion().setFromEuler(new THREE.Euler(0, heading, dead ? 1.4 : 0)),
+                        new THREE.Vector3(size, size, size)
                     );
-                    /* m.identity()
-                    m.makeTranslation(i*10, 10, 0) */
-                    this.wolves.setMatrixAt(updated_wolf,
-                        m
-                    )
-                    /* let other = new THREE.Matrix4();
-                    this.wolves.getMatrixAt(updated_wolf, other)
-                    log(other == m, other, m) */
-                    updated_wolf++;
                     break;
-                case 1:
+                }
+                case "Plant": {
+                    const size = agents.vitals[i][0] / 100;
                     m.compose(
-                        new THREE.Vector3(
-                            agents.positions[i][0] - this.size / 2,
-                            agents.positions[i][2] * 12.5,
-                            agents.positions[i][1] - this.size / 2
-                        ),
-                        new THREE.Quaternion().setFromEuler(
-                            new THREE.Euler(
-                                0,
-                                Math.lerp(prevrot, Math.atan2(agents.accelerations[i][0], agents.accelerations[i][1]), .1),
-                                dead ? 1.4 : 0
-                            )
-                        ),
-                        new THREE.Vector3(agents.genotypes[i][0] / 10, agents.genotypes[i][0] / 10, agents.genotypes[i][0] / 10)
+                        position,
+                        new THREE.Quaternion().setFromEuler(new THREE.Euler(0, agents.positions[i][0] * 3000, 0)),
+                        new THREE.Vector3(size, size, size)
                     );
-                    this.sheep.setMatrixAt(updated_sheep,
-                        m
-                    )
-                    updated_sheep++;
                     break;
-                case 2:
+                }
+                case "Carcass": {
+                    // Shrinks as the carcass is eaten and rots away
+                    const size = Math.cbrt(Math.max(agents.vitals[i][0], 0)) * 1.5;
                     m.compose(
-                        new THREE.Vector3(
-                            agents.positions[i][0] - this.size / 2,
-                            agents.positions[i][2] * 12.5,
-                            agents.positions[i][1] - this.size / 2
-                        ),
-                        new THREE.Quaternion().setFromEuler(
-                            new THREE.Euler(
-                                0,
-                                agents.positions[i][0] * 3000,
-                                0
-                            )
-                        ),
-                        new THREE.Vector3(agents.vitals[i][0] / 100, agents.vitals[i][0] / 100, agents.vitals[i][0] / 100)
-                    )
-                    this.grass.setMatrixAt(updated_grass,
-                        m
-                    )
-                    updated_grass++;
+                        position,
+                        new THREE.Quaternion().setFromEuler(new THREE.Euler(0, agents.positions[i][0] * 3000, 0)),
+                        new THREE.Vector3(size, size / 2, size)
+                    );
                     break;
+                }
             }
+            entry.mesh.setMatrixAt(entry.count, m);
+            entry.count++;
             /* log(`Updated instance ${i} with matrix ${m.elements}`) */
         }
         const nil = new THREE.Matrix4();
         nil.makeTranslation(0, -100, 0);
-        for (let i = updated_wolf; i < this.wolfNumber; i++) {
-            this.wolves.setMatrixAt(i, nil);
-        }
-        for (let i = updated_sheep; i < this.sheepNumber; i++) {
-            this.sheep.setMatrixAt(i, nil);
-        }
-        for (let i = updated_grass; i < this.grassNumber; i++) {
-            this.grass.setMatrixAt(i, nil);
+        for (const entry of this.species_meshes) {
+            if (!entry) continue;
+            for (let i = entry.count; i < entry.capacity; i++) {
+                entry.mesh.setMatrixAt(i, nil);
+            }
+            entry.mesh.instanceMatrix.needsUpdate = true;
         }
-
-        /* log(`Updated ${updated_instances} instances.`) */
-        this.sheep.instanceMatrix.needsUpdate = true;
-        this.wolves.instanceMatrix.needsUpdate = true;
-        this.grass.instanceMatrix.needsUpdate = true;
     }
 
     async load_models() {
         this.done_loading = false;
         const loader = new GLTFLoader();
 
-        this.sheep_model = (await loader.loadAsync("./rsc/models/sheep.glb")).scene.children[0]
-        /* log("sheep", this.sheep_model) */
-        this.wolf_model = (await loader.loadAsync("./rsc/models/wolf.glb")).scene.children[0]
-        /* log("wolf", this.wolf_model) */
-        this.grass_model = (await loader.loadAsync("./rsc/models/grass.glb")).scene.children[0]
-        /* log("grass", this.grass_model) */
-
-        this.sheep = new THREE.InstancedMesh(
-            this.sheep_model.geometry.scale(1.6, 1.6, 1.6),
-            this.sheep_model.material,
-            this.sheepNumber
-        )
-        this.sheep.castShadow = true;
-        this.sheep.receiveShadow = true;
-        this.scene.add(this.sheep)
-
-        this.wolves = new THREE.InstancedMesh(
-            this.wolf_model.geometry.scale(2, 2, 2),
-            this.wolf_model.material,
-            this.wolfNumber
-        )
-        this.wolves.castShadow = true;
-        this.wolves.receiveShadow = true;
-        this.scene.add(this.wolves)
-
-        this.grass = new THREE.InstancedMesh(
-            this.grass_model.geometry.scale(4, 4, 4),
-            this.grass_model.material,
-            MAX_GRASS
-        )
-        this.grass.castShadow = true;
-        this.grass.receiveShadow = true;
-        this.scene.add(this.grass)
-
-        // Put each matrix's children at nil position
-        const nil = new THREE.Matrix4().makeTranslation(0, -50, 0);
-        for (let i = 0; i < this.sheepNumber; i++) {
-            this.sheep.setMatrixAt(i, nil);
-        }
-        for (let i = 0; i < this.wolfNumber; i++) {
-            this.wolves.setMatrixAt(i, nil);
-        }
-        for (let i = 0; i < this.grass.count; i++) {
-            this.grass.setMatrixAt(i, nil)
+        this.models = {};
+        for (const [name, model] of Object.entries(SPECIES_MODELS)) {
+            const mesh = (await loader.loadAsync(model.path)).scene.children[0];
+            mesh.geometry.scale(model.scale, model.scale, model.scale);
+            mesh.material.envMap = this.exr;
+            mesh.material.envMapIntensity = .5;
+            this.models[name] = mesh;
         }
 
-        this.wolf_model.material.envMap = this.exr;
-        this.sheep_model.material.envMap = this.exr;
-        this.grass_model.material.envMap = this.exr;
-        this.ground.material.envMap = this.exr;
+        this.species_meshes = [];
+        this.refresh_species();
 
-        this.wolf_model.material.envMapIntensity = .5;
-        this.sheep_model.material.envMapIntensity = .5;
-        this.grass_model.material.envMapIntensity = .5;
+        this.ground.material.envMap = this.exr;
         this.ground.material.envMapIntensity = 0.5;
         /* log(this.ground.material) */
 
@@ -320,6 +244,84 @@ export class Renderer {
         this.done_loading = true;
     }
 
+    // Makes an instanced mesh for every species the world knows that doesn't have one yet
+    refresh_species() {
+        this.species = this.world.get_species();
+        for (const species of this.species) {
+            if (this.species_meshes[species.id]) continue;
+
+            const model = this.models[species.name];
+            const [geometry, material] = model
+                ? [model.geometry, model.material]
+                : this.role_shape(species);
+            const capacity = species.role == "Carcass"
+                ? MAX_CARCASSES
+                : Math.max(species.population, MIN_INSTANCES);
+            const entry = { role: species.role, geometry, material, capacity: 0, count: 0 };
+            this.create_instances(entry, capacity);
+            this.species_meshes[species.id] = entry;
+        }
+    }
+
+    // Plain stand-in for species without a model
+    role_shape(species) {
+        switch (species.role) {
+            case "Animal":
+                return [
+                    new THREE.CapsuleGeometry(.6, 1.2, 4, 8).rotateX(Math.PI / 2).translate(0, .6, 0),
+                    new THREE.MeshStandardMaterial({
+                        color: SPECIES_PALETTE[species.id % SPECIES_PALETTE.length],
+                        roughness: .9,
+                    }),
+                ];
+            case "Plant":
+                return [
+                    new THREE.ConeGeometry(.6, 1.6, 6).translate(0, .8, 0),
+                    new THREE.MeshStandardMaterial({
+                        color: 0x3f7f2a,
+                        roughness: 1,
+                    }),
+                ];
+            default:
+                // No model for carcasses, a flattened lump of meat does
+                return [
+                    new THREE.DodecahedronGeometry(1, 0),
+                    new THREE.MeshStandardMaterial({
+                        color: 0x5a1e14,
+                        roughness: .8,
+                    }),
+                ];
+        }
+    }
+
+    create_instances(entry, capacity) {
+        const mesh = new THREE.InstancedMesh(entry.geometry, entry.material, capacity);
+        mesh.castShadow = true;
+        mesh.receiveShadow = true;
+
+        // Put each instance at nil position
+        const nil = new THREE.Matrix4().makeTranslation(0, -50, 0);
+        for (let i = 0; i < capacity; i++) {
+            mesh.setMatrixAt(i, nil);
+        }
+        this.scene.add(mesh);
+        entry.mesh = mesh;
+        entry.capacity = capacity;
+    }
+
+    // Instanced meshes have a fixed size, swap in one twice as big when a species outgrows it
+    grow_species_mesh(entry) {
+        const old = entry.mesh;
+        this.create_instances(entry, entry.capacity * 2);
+        const m = new THREE.Matrix4();
+        for (let i = 0; i < entry.count; i++) {
+            old.getMatrixAt(i, m);
+            entry.mesh.setMatrixAt(i, m);
+        }
+        this.scene.remove(old);
+        old.dispose();
+    }
+
     async load_lights() {
         this.sun = new THREE.DirectionalLight(0xffffff, 1.2);
         this.sun.castShadow = true;
@@ -329,10 +331,10 @@ export class Renderer {
         this.sun.lookAt(new THREE.Vector3(0, 0, 0));
         this.sun.shadow.camera.far = 5000;
         this.sun.shadow.camera.near = 500;
-        this.sun.shadow.camera.bottom = -this.size / 2;
-        this.sun.shadow.camera.top = this.size / 2;
-        this.sun.shadow.camera.left = -this.size / 2;
-        this.sun.shadow.camera.right = this.size / 2;
+        this.sun.shadow.camera.bottom = -this.height / 2;
+        this.sun.shadow.camera.top = this.height / 2;
+        this.sun.shadow.camera.left = -this.width / 2;
+        this.sun.shadow.camera.right = this.width / 2;
         this.sun.shadow.bias = 0.01
 
         /* this.scene.add(new THREE.CameraHelper(this.sun.shadow.camera)); */
@@ -360,8 +362,8 @@ export class Renderer {
         for (let i = 0; i < this.ground.geometry.attributes.position.array.length; i += 3) {
             this.ground.geometry.attributes.position.array[i + 1] +=
                 this.custom_noise(
-                    this.ground.geometry.attributes.position.array[i] + this.size / 2,
-                    this.ground.geometry.attributes.position.array[i + 2] + this.size / 2
+                    this.ground.geometry.attributes.position.array[i] + this.center[0],
+                    this.ground.geometry.attributes.position.array[i + 2] + this.center[1]
                 ) * 12.5;
 
             /* log(this.ground.geometry.attributes.position.array[i + 2]) */
@@ -383,8 +385,18 @@ export class Renderer {
         if (this.done_loading) {
             this.update_agents(agents)
         }
+        this.update_lighting(this.world.get_environment())
         this.renderer.render(this.scene, this.camera);
     }
+
+    update_lighting(environment) {
+        // Dim the sun at night and swing it across the sky over the day
+        this.sun.intensity = .2 + 1.0 * environment.daylight;
+        const angle = environment.time_of_day * Math.PI * 2;
+        this.sun.position.x = Math.sin(angle) * this.size;
+        this.sun.position.y = Math.max(-Math.cos(angle), .1) * this.size;
+        this.sun.lookAt(new THREE.Vector3(0, 0, 0));
+    }
 }
 
 export default Renderer
\ No newline at end of file
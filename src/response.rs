@@ -0,0 +1,231 @@
+use serde::{Deserialize, Serialize};
+
+use crate::species::{Role, SpeciesId, SpeciesRegistry};
+
+// Area prey densities are measured in, a 100 by 100 patch
+const DENSITY_AREA: f32 = 10000.;
+// Width of the prey density bins kills are recorded in
+const DENSITY_BIN: f32 = 0.25;
+// Densities past the last bin are lumped into it
+const MAX_BINS: usize = 400;
+
+// Holling's functional responses
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum ResponseType {
+    // Kills grow linearly with prey density until the predator is full
+    Linear,
+    // Handling each kill caps the kill rate
+    Saturating,
+    // Predators barely bother with rare prey and get better at finding it as it gets common
+    Sigmoid,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FunctionalResponse {
+    pub kind: ResponseType,
+    // Share of the sight distance searched for prey, per prey per unit area for sigmoid
+    pub attack_rate: f32,
+    // Time spent dealing with a kill before going after live prey again
+    pub handling_time: f32,
+    // Hunger above which the predator stops hunting
+    pub satiation: f32,
+}
+
+// Predators search their whole sight and go straight back to hunting after a kill, until
+// they're full
+impl Default for FunctionalResponse {
+    fn default() -> Self {
+        FunctionalResponse {
+            kind: ResponseType::Linear,
+            attack_rate: 1.,
+            handling_time: 0.,
+            satiation: 40.,
+        }
+    }
+}
+
+impl FunctionalResponse {
+    pub fn handling_time(&self) -> f32 {
+        match self.kind {
+            ResponseType::Linear => 0.,
+            ResponseType::Saturating | ResponseType::Sigmoid => self.handling_time.max(0.),
+        }
+    }
+
+    // How far the predator notices live prey, given how many are in sight
+    pub fn search_radius(&self, sight_distance: f32, prey_in_sight: usize) -> f32 {
+        let share = match self.kind {
+            ResponseType::Linear | ResponseType::Saturating => self.attack_rate,
+            ResponseType::Sigmoid => {
+                let area = std::f32::consts::PI * sight_distance.powi(2);
+                let density = prey_in_sight as f32 / area.max(1.) * DENSITY_AREA;
+                self.attack_rate * density
+            }
+        };
+        sight_distance * share.clamp(0., 1.)
+    }
+}
+
+// Live animals each species preys on per density area, None for species that don't hunt
+pub fn prey_densities(
+    species: &SpeciesRegistry,
+    populations: &[usize],
+    world_area: f32,
+) -> Vec<Option<f32>> {
+    species
+        .species
+        .iter()
+        .map(|predator| {
+            let prey: Vec<SpeciesId> = predator
+                .diet
+                .iter()
+                .filter(|food| species.get(food.species).role == Role::Animal)
+                .map(|food| food.species)
+                .collect();
+            if predator.role != Role::Animal || prey.is_empty() {
+                return None;
+            }
+            let count: usize = prey
+                .iter()
+                .map(|id| populations.get(*id).copied().unwrap_or(0))
+                .sum();
+            Some(count as f32 / world_area.max(1.) * DENSITY_AREA)
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, Default)]
+struct DensityBin {
+    predator_steps: u32,
+    kills: u32,
+}
+
+// Kills made by every predator species, binned by the density of its live prey at the time
+pub struct ResponseLog {
+    bins: Vec<Vec<DensityBin>>,
+}
+
+impl ResponseLog {
+    pub fn new() -> ResponseLog {
+        ResponseLog { bins: Vec::new() }
+    }
+
+    pub fn clear(&mut self) {
+        self.bins.clear();
+    }
+
+    // Adds a step of one predator at the given prey density
+    pub fn record(&mut self, predator: SpeciesId, density: f32, kills: u32) {
+        if self.bins.len() <= predator {
+            self.bins.resize(predator + 1, Vec::new());
+        }
+        let bins = &mut self.bins[predator];
+        let index = ((density / DENSITY_BIN) as usize).min(MAX_BINS - 1);
+        if bins.len() <= index {
+            bins.resize(index + 1, DensityBin::default());
+        }
+        bins[index].predator_steps += 1;
+        bins[index].kills += kills;
+    }
+
+    pub fn statistics(&self, species: &SpeciesRegistry) -> Vec<SerializedResponse> {
+        self.bins
+            .iter()
+            .enumerate()
+            .filter(|(_, bins)| !bins.is_empty())
+            .map(|(predator, bins)| SerializedResponse {
+                predator: species.get(predator).name.clone(),
+                response: species.get(predator).response,
+                bins: bins
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, bin)| bin.predator_steps > 0)
+                    .map(|(index, bin)| SerializedDensityBin {
+                        density: (index as f32 + 0.5) * DENSITY_BIN,
+                        predator_steps: bin.predator_steps,
+                        kills: bin.kills,
+                        kill_rate: bin.kills as f32 / bin.predator_steps as f32,
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SerializedDensityBin {
+    // Live prey per 100 by 100 area, middle of the bin
+    pub density: f32,
+    pub predator_steps: u32,
+    pub kills: u32,
+    // Kills per predator per step
+    pub kill_rate: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SerializedResponse {
+    pub predator: String,
+    pub response: FunctionalResponse,
+    pub bins: Vec<SerializedDensityBin>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::species::{GRASS, SHEEP, WOLF};
+
+    fn response(kind: ResponseType, attack_rate: f32) -> FunctionalResponse {
+        FunctionalResponse {
+            kind,
+            attack_rate,
+            ..FunctionalResponse::default()
+        }
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn linear_and_saturating_search_a_fixed_share_of_sight() {
+        for kind in [ResponseType::Linear, ResponseType::Saturating] {
+            assert!(close(response(kind, 0.5).search_radius(100., 0), 50.));
+            assert!(close(response(kind, 0.5).search_radius(100., 50), 50.));
+            assert!(close(response(kind, 2.).search_radius(100., 1), 100.));
+        }
+    }
+
+    #[test]
+    fn sigmoid_search_grows_with_prey_in_sight() {
+        let sigmoid = response(ResponseType::Sigmoid, 0.1);
+        // 10 prey in a circle of radius 100 are about 3.18 per density area
+        let density = 10. / (std::f32::consts::PI * 100f32.powi(2)) * DENSITY_AREA;
+        assert!(close(sigmoid.search_radius(100., 0), 0.));
+        assert!(close(sigmoid.search_radius(100., 10), 100. * 0.1 * density));
+        assert!(close(sigmoid.search_radius(100., 1000), 100.));
+    }
+
+    #[test]
+    fn prey_densities_count_live_animal_prey_only() {
+        let species = SpeciesRegistry::new(10, 2, 100);
+        let mut populations = vec![0; species.species.len()];
+        populations[WOLF] = 2;
+        populations[SHEEP] = 10;
+        populations[GRASS] = 100;
+        let densities = prey_densities(&species, &populations, 1000. * 1000.);
+        // Wolves count sheep but not carcasses, sheep only eat plants
+        assert!(close(densities[WOLF].unwrap(), 0.1));
+        assert_eq!(densities[SHEEP], None);
+        assert_eq!(densities[GRASS], None);
+    }
+
+    #[test]
+    fn record_lumps_extreme_densities_into_the_last_bin() {
+        let mut log = ResponseLog::new();
+        log.record(WOLF, 1e9, 1);
+        log.record(WOLF, f32::INFINITY, 1);
+        assert_eq!(log.bins[WOLF].len(), MAX_BINS);
+        assert_eq!(log.bins[WOLF][MAX_BINS - 1].kills, 2);
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use rand::{rngs::ThreadRng, Rng};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    agent::Agent,
+    behaviour::{closest_threat, distance_squared, Context},
+    species::{Role, SpeciesRegistry},
+};
+
+// Calls carry this far, as long as the listener has the caller in sight
+const ALARM_RANGE: f32 = 80.;
+// How long a call keeps ringing out, and how long before the caller can call again
+const ALARM_DURATION: f32 = 3.;
+const ALARM_COOLDOWN: f32 = 5.;
+const ALARM_STAMINA_COST: f32 = 2.;
+// Predators favour prey that is calling this much more
+pub const CALLER_CONSPICUOUSNESS: f32 = 2.;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Alarm {
+    // Where the caller saw the predator
+    pub threat: (f32, f32),
+    pub time: f32,
+}
+
+pub fn is_calling(agent: &Agent, time: f32) -> bool {
+    agent
+        .alarm
+        .is_some_and(|alarm| time - alarm.time < ALARM_DURATION)
+}
+
+// Animals that spot a predator may warn the others of their kind, costing them stamina and
+// drawing the predator's attention
+pub fn update_alarm(agent: &mut Agent, context: &Context, thread_rng: &mut ThreadRng) {
+    if let Some(alarm) = agent.alarm {
+        if context.time - alarm.time < ALARM_COOLDOWN {
+            return;
+        }
+    }
+    let threat = match closest_threat(agent, context) {
+        Some(threat) => (threat.position.0, threat.position.1),
+        None => return,
+    };
+    if thread_rng.gen::<f32>() >= context.genotype.alarm_propensity {
+        return;
+    }
+    agent.alarm = Some(Alarm {
+        threat,
+        time: context.time,
+    });
+    agent.calls += 1;
+    agent.stamina = (agent.stamina - ALARM_STAMINA_COST).max(0.);
+}
+
+// Position of the predator the closest calling member of the agent's kind is warning about
+pub fn heard_alarm(agent: &Agent, context: &Context) -> Option<(f32, f32)> {
+    let mut closest_distance = f32::MAX;
+    let mut closest = None;
+    for other in context.neighbours(agent) {
+        if other.dead
+            || other.kind.species() != agent.kind.species()
+            || !is_calling(other, context.time)
+        {
+            continue;
+        }
+        let distance = distance_squared(agent, other);
+        if distance < ALARM_RANGE.powi(2) && distance < closest_distance {
+            closest_distance = distance;
+            closest = other.alarm.map(|alarm| alarm.threat);
+        }
+    }
+    closest
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SerializedAlarms {
+    pub species: String,
+    // Living animals calling right now
+    pub callers: usize,
+    // Calls made over their lives by the living animals
+    pub calls: u32,
+    pub mean_propensity: f32,
+}
+
+pub fn statistics(
+    agents: &HashMap<Uuid, Agent>,
+    species: &SpeciesRegistry,
+    time: f32,
+) -> Vec<SerializedAlarms> {
+    species
+        .species
+        .iter()
+        .filter(|species| species.role == Role::Animal)
+        .map(|species| {
+            let mut result = SerializedAlarms {
+                species: species.name.clone(),
+                callers: 0,
+                calls: 0,
+                mean_propensity: 0.,
+            };
+            let mut count = 0;
+            for agent in agents.values() {
+                let genotype = match agent.kind.genotype() {
+                    Some(genotype) if !agent.dead && agent.kind.species() == species.id => genotype,
+                    _ => continue,
+                };
+                if is_calling(agent, time) {
+                    result.callers += 1;
+                }
+                result.calls += agent.calls;
+                result.mean_propensity += genotype.alarm_propensity;
+                count += 1;
+            }
+            if count > 0 {
+                result.mean_propensity /= count as f32;
+            }
+            result
+        })
+        .collect()
+}
@@ -0,0 +1,323 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    agent::AgentType,
+    genes::{GeneSchema, Genotype},
+    response::FunctionalResponse,
+};
+
+pub type SpeciesId = usize;
+
+// Built in species, the renderer has models for them and picks others by role
+pub const WOLF: SpeciesId = 0;
+pub const SHEEP: SpeciesId = 1;
+pub const GRASS: SpeciesId = 2;
+pub const CARCASS: SpeciesId = 3;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Role {
+    Animal,
+    Plant,
+    Carcass,
+}
+
+// One entry of a diet, higher preferences are sought out first
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Food {
+    pub species: SpeciesId,
+    pub preference: f32,
+}
+
+#[derive(Deserialize)]
+pub struct FoodDefinition {
+    pub name: String,
+    #[serde(default = "default_preference")]
+    pub preference: f32,
+}
+
+fn default_preference() -> f32 {
+    1.
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BehaviourProfile {
+    pub flocks: bool,
+    pub hunts_in_packs: bool,
+    // Settles around a den and keeps others of its kind out
+    pub territorial: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Species {
+    pub id: SpeciesId,
+    pub name: String,
+    pub role: Role,
+    pub diet: Vec<Food>,
+    // Species this one runs from, on top of anything that eats it
+    pub predators: Vec<SpeciesId>,
+    pub behaviour: BehaviourProfile,
+    pub genes: GeneSchema,
+    // How the kill rate of a predator follows prey density
+    pub response: FunctionalResponse,
+    // Number of individuals spawned when the world is created
+    pub population: usize,
+}
+
+// A species as described from JS, other species are referred to by name
+#[derive(Deserialize)]
+pub struct SpeciesDefinition {
+    pub name: String,
+    pub role: Role,
+    #[serde(default)]
+    pub diet: Vec<FoodDefinition>,
+    #[serde(default)]
+    pub predators: Vec<String>,
+    #[serde(default)]
+    pub behaviour: BehaviourProfile,
+    #[serde(default)]
+    pub genes: GeneSchema,
+    #[serde(default)]
+    pub response: FunctionalResponse,
+    #[serde(default)]
+    pub population: usize,
+}
+
+pub struct SpeciesRegistry {
+    pub species: Vec<Species>,
+}
+
+impl SpeciesRegistry {
+    // The classic wolves, sheep and grass food chain
+    pub fn new(sheep_num: usize, wolf_num: usize, grass_num: usize) -> SpeciesRegistry {
+        let species = vec![
+            Species {
+                id: WOLF,
+                name: "Wolf".to_string(),
+                role: Role::Animal,
+                diet: vec![
+                    Food {
+                        species: SHEEP,
+                        preference: 1.,
+                    },
+                    Food {
+                        species: CARCASS,
+                        preference: 0.5,
+                    },
+                ],
+                predators: Vec::new(),
+                behaviour: BehaviourProfile {
+                    flocks: false,
+                    hunts_in_packs: true,
+                    // Dens can be switched on from JS with set_territorial
+                    territorial: false,
+                },
+                genes: GeneSchema::default(),
+                response: FunctionalResponse::default(),
+                population: wolf_num,
+            },
+            Species {
+                id: SHEEP,
+                name: "Sheep".to_string(),
+                role: Role::Animal,
+                diet: vec![Food {
+                    species: GRASS,
+                    preference: 1.,
+                }],
+                predators: Vec::new(),
+                behaviour: BehaviourProfile {
+                    flocks: true,
+                    hunts_in_packs: false,
+                    territorial: false,
+                },
+                genes: GeneSchema::default(),
+                response: FunctionalResponse::default(),
+                population: sheep_num,
+            },
+            Species {
+                id: GRASS,
+                name: "Grass".to_string(),
+                role: Role::Plant,
+                diet: Vec::new(),
+                predators: Vec::new(),
+                behaviour: BehaviourProfile::default(),
+                genes: GeneSchema::default(),
+                response: FunctionalResponse::default(),
+                population: grass_num,
+            },
+            Species {
+                id: CARCASS,
+                name: "Carcass".to_string(),
+                role: Role::Carcass,
+                diet: Vec::new(),
+                predators: Vec::new(),
+                behaviour: BehaviourProfile::default(),
+                genes: GeneSchema::default(),
+                response: FunctionalResponse::default(),
+                population: 0,
+            },
+        ];
+        SpeciesRegistry { species }
+    }
+
+    pub fn find(&self, name: &str) -> Option<SpeciesId> {
+        self.species
+            .iter()
+            .find(|species| species.name == name)
+            .map(|species| species.id)
+    }
+
+    fn resolve(&self, names: &[String]) -> Result<Vec<SpeciesId>, String> {
+        names
+            .iter()
+            .map(|name| {
+                self.find(name)
+                    .ok_or_else(|| format!("Unknown species {}", name))
+            })
+            .collect()
+    }
+
+    pub fn register(&mut self, definition: SpeciesDefinition) -> Result<SpeciesId, String> {
+        if self.find(&definition.name).is_some() {
+            return Err(format!("Species {} already exists", definition.name));
+        }
+        if definition.role == Role::Carcass {
+            return Err("There can only be one carcass species".to_string());
+        }
+        let id = self.species.len();
+        let mut diet = Vec::new();
+        for food in definition.diet.iter() {
+            diet.push(Food {
+                species: self
+                    .find(&food.name)
+                    .ok_or_else(|| format!("Unknown species {}", food.name))?,
+                preference: food.preference.max(0.),
+            });
+        }
+        let predators = self.resolve(&definition.predators)?;
+        self.species.push(Species {
+            id,
+            name: definition.name,
+            role: definition.role,
+            diet,
+            predators,
+            behaviour: definition.behaviour,
+            genes: definition.genes,
+            response: definition.response,
+            population: definition.population,
+        });
+        Ok(id)
+    }
+
+    // Lets an existing species feed on a newly registered one, or changes how much it likes it
+    pub fn add_prey(&mut self, predator: SpeciesId, prey: SpeciesId, preference: f32) {
+        if let Some(species) = self.species.get_mut(predator) {
+            species.diet.retain(|food| food.species != prey);
+            species.diet.push(Food {
+                species: prey,
+                preference: preference.max(0.),
+            });
+        }
+    }
+
+    pub fn get(&self, id: SpeciesId) -> &Species {
+        &self.species[id]
+    }
+
+    pub fn name(&self, kind: &AgentType) -> &str {
+        &self.get(kind.species()).name
+    }
+
+    fn in_diet(&self, eater: SpeciesId, food: SpeciesId) -> bool {
+        self.get(eater).diet.iter().any(|f| f.species == food)
+    }
+
+    // Preference the species gives to a food, 0 if it's not part of the diet
+    pub fn preference(&self, eater: &AgentType, food: &AgentType) -> f32 {
+        if !eater.is_animal() {
+            return 0.;
+        }
+        self.get(eater.species())
+            .diet
+            .iter()
+            .find(|f| f.species == food.species())
+            .map_or(0., |f| f.preference)
+    }
+
+    pub fn eats(&self, eater: &AgentType, food: &AgentType) -> bool {
+        self.preference(eater, food) > 0.
+    }
+
+    // Omnivores have both plants and meat in their diet
+    pub fn is_omnivore(&self, id: SpeciesId) -> bool {
+        let diet = &self.get(id).diet;
+        diet.iter().any(|f| self.get(f.species).role == Role::Plant)
+            && diet.iter().any(|f| self.get(f.species).role != Role::Plant)
+    }
+
+    // How much an omnivore's carnivory gene leans it towards this food, 0.5 is balanced
+    fn lean(&self, eater: &AgentType, genotype: &Genotype, food: &AgentType) -> f32 {
+        if !self.is_omnivore(eater.species()) {
+            return 0.5;
+        }
+        if food.is_plant() {
+            1. - genotype.carnivory
+        } else {
+            genotype.carnivory
+        }
+    }
+
+    // How much this individual wants the food, the species preference shifted by its genes,
+    // nutritious plants are sought out and toxins it can't handle put it off
+    pub fn desirability(&self, eater: &AgentType, genotype: &Genotype, food: &AgentType) -> f32 {
+        let mut desirability = self.preference(eater, food) * 2. * self.lean(eater, genotype, food);
+        if let Some(plant) = food.plant_genotype() {
+            desirability *= plant.nutrition
+                * (1. - (plant.toxicity - genotype.toxin_tolerance).max(0.)).max(0.);
+        }
+        desirability
+    }
+
+    // Specialised omnivores digest the food they lean towards better, generalists are
+    // mediocre at both
+    pub fn digestion(&self, eater: &AgentType, genotype: &Genotype, food: &AgentType) -> f32 {
+        2. * self.lean(eater, genotype, food)
+    }
+
+    // Animals are afraid of anything alive that would eat them or is listed as a predator
+    pub fn fears(&self, animal: &AgentType, other: &AgentType) -> bool {
+        other.is_animal()
+            && (self
+                .get(animal.species())
+                .predators
+                .contains(&other.species())
+                || self.eats(other, animal))
+    }
+
+    pub fn flocks(&self, kind: &AgentType) -> bool {
+        kind.is_animal() && self.get(kind.species()).behaviour.flocks
+    }
+
+    pub fn hunts_in_packs(&self, kind: &AgentType) -> bool {
+        kind.is_animal() && self.get(kind.species()).behaviour.hunts_in_packs
+    }
+
+    pub fn is_territorial(&self, kind: &AgentType) -> bool {
+        kind.is_animal() && self.get(kind.species()).behaviour.territorial
+    }
+
+    // Species an animal has to keep an eye on, its own kind, its food and its predators
+    pub fn visible_to(&self, id: SpeciesId) -> Vec<SpeciesId> {
+        let species = self.get(id);
+        self.species
+            .iter()
+            .filter(|other| {
+                other.id == id
+                    || self.in_diet(id, other.id)
+                    || species.predators.contains(&other.id)
+                    || self.in_diet(other.id, id)
+            })
+            .map(|other| other.id)
+            .collect()
+    }
+}
@@ -0,0 +1,661 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{normalize_vector, vector_length, Bounds};
+
+const NAV_CELL_SIZE: f32 = 16.;
+// Max number of nodes A* is allowed to expand before giving up on a path
+const MAX_SEARCH_NODES: usize = 4096;
+// How far past the edge points are pushed out of obstacles, so they are clearly outside
+const PUSH_OUT_MARGIN: f32 = 0.01;
+// Bisection steps used to find where a move first hits an obstacle
+const SWEEP_ITERATIONS: usize = 12;
+// How many rings of cells are searched for a free cell around a blocked one
+const MAX_SNAP_DISTANCE: i32 = 4;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Obstacle {
+    Rock {
+        position: (f32, f32),
+        radius: f32,
+    },
+    Fence {
+        start: (f32, f32),
+        end: (f32, f32),
+        thickness: f32,
+    },
+    Polygon {
+        points: Vec<(f32, f32)>,
+    },
+}
+
+impl Obstacle {
+    pub fn contains(&self, point: (f32, f32), margin: f32) -> bool {
+        match self {
+            Obstacle::Rock { position, radius } => {
+                vector_length((point.0 - position.0, point.1 - position.1)) < radius + margin
+            }
+            Obstacle::Fence {
+                start,
+                end,
+                thickness,
+            } => segment_distance(point, *start, *end) < thickness / 2. + margin,
+            Obstacle::Polygon { points } => {
+                if point_in_polygon(point, points) {
+                    return true;
+                }
+                if margin <= 0. {
+                    return false;
+                }
+                for i in 0..points.len() {
+                    let next = points[(i + 1) % points.len()];
+                    if segment_distance(point, points[i], next) < margin {
+                        return true;
+                    }
+                }
+                false
+            }
+        }
+    }
+
+    // Whether the segment between two points touches the obstacle
+    pub fn intersects_segment(&self, from: (f32, f32), to: (f32, f32)) -> bool {
+        match self {
+            Obstacle::Rock { position, radius } => segment_distance(*position, from, to) < *radius,
+            Obstacle::Fence {
+                start,
+                end,
+                thickness,
+            } => segments_distance(from, to, *start, *end) < thickness / 2.,
+            Obstacle::Polygon { points } => {
+                if point_in_polygon(from, points) || point_in_polygon(to, points) {
+                    return true;
+                }
+                (0..points.len()).any(|i| {
+                    segments_intersect(from, to, points[i], points[(i + 1) % points.len()])
+                })
+            }
+        }
+    }
+
+    // Returns the point pushed just past the edge of the obstacle, or None if the point is
+    // outside of it
+    pub fn push_out(&self, point: (f32, f32)) -> Option<(f32, f32)> {
+        if !self.contains(point, 0.) {
+            return None;
+        }
+        match self {
+            Obstacle::Rock { position, radius } => {
+                let mut away = (point.0 - position.0, point.1 - position.1);
+                if vector_length(away) == 0. {
+                    away = (1., 0.);
+                }
+                let away = normalize_vector(away);
+                let distance = radius + PUSH_OUT_MARGIN;
+                Some((
+                    position.0 + away.0 * distance,
+                    position.1 + away.1 * distance,
+                ))
+            }
+            Obstacle::Fence {
+                start,
+                end,
+                thickness,
+            } => {
+                let closest = closest_point_on_segment(point, *start, *end);
+                let mut away = (point.0 - closest.0, point.1 - closest.1);
+                if vector_length(away) == 0. {
+                    // Standing right on the fence line, push along the fence normal
+                    away = (-(end.1 - start.1), end.0 - start.0);
+                }
+                let away = normalize_vector(away);
+                let distance = thickness / 2. + PUSH_OUT_MARGIN;
+                Some((closest.0 + away.0 * distance, closest.1 + away.1 * distance))
+            }
+            Obstacle::Polygon { points } => {
+                let mut closest = point;
+                let mut closest_edge = (point, point);
+                let mut closest_distance = f32::MAX;
+                for i in 0..points.len() {
+                    let edge = (points[i], points[(i + 1) % points.len()]);
+                    let candidate = closest_point_on_segment(point, edge.0, edge.1);
+                    let distance = vector_length((point.0 - candidate.0, point.1 - candidate.1));
+                    if distance < closest_distance {
+                        closest_distance = distance;
+                        closest = candidate;
+                        closest_edge = edge;
+                    }
+                }
+                // A point right on the edge is ambiguous, step a little further out
+                let mut out = (closest.0 - point.0, closest.1 - point.1);
+                if vector_length(out) == 0. {
+                    let (start, end) = closest_edge;
+                    out = (end.1 - start.1, -(end.0 - start.0));
+                }
+                let out = normalize_vector(out);
+                let mut pushed = (
+                    closest.0 + out.0 * PUSH_OUT_MARGIN,
+                    closest.1 + out.1 * PUSH_OUT_MARGIN,
+                );
+                if point_in_polygon(pushed, points) {
+                    pushed = (
+                        closest.0 - out.0 * PUSH_OUT_MARGIN,
+                        closest.1 - out.1 * PUSH_OUT_MARGIN,
+                    );
+                }
+                Some(pushed)
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+struct SearchNode {
+    cost: f32,
+    cell: (usize, usize),
+}
+impl Eq for SearchNode {}
+impl Ord for SearchNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed so the BinaryHeap pops the cheapest node first
+        other
+            .cost
+            .partial_cmp(&self.cost)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+impl PartialOrd for SearchNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Clone)]
+pub struct Terrain {
+    pub obstacles: Vec<Obstacle>,
+    bounds: Bounds,
+    columns: usize,
+    rows: usize,
+    blocked: Vec<bool>,
+}
+
+impl Terrain {
+    pub fn new(bounds: Bounds) -> Terrain {
+        let columns = (bounds.width / NAV_CELL_SIZE).ceil().max(1.) as usize;
+        let rows = (bounds.height / NAV_CELL_SIZE).ceil().max(1.) as usize;
+        Terrain {
+            obstacles: Vec::new(),
+            bounds,
+            columns,
+            rows,
+            blocked: vec![false; columns * rows],
+        }
+    }
+
+    pub fn add_obstacle(&mut self, obstacle: Obstacle) {
+        self.obstacles.push(obstacle);
+        self.build_navigation_grid();
+    }
+
+    pub fn clear(&mut self) {
+        self.obstacles.clear();
+        self.build_navigation_grid();
+    }
+
+    fn build_navigation_grid(&mut self) {
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let center = self.cell_center((column, row));
+                self.blocked[row * self.columns + column] = self
+                    .obstacles
+                    .iter()
+                    .any(|o| o.contains(center, NAV_CELL_SIZE / 2.));
+            }
+        }
+    }
+
+    pub fn is_blocked(&self, point: (f32, f32)) -> bool {
+        self.obstacles.iter().any(|o| o.contains(point, 0.))
+    }
+
+    // Moves a point that ended up inside an obstacle back onto its edge
+    pub fn resolve_collision(&self, mut point: (f32, f32)) -> (f32, f32) {
+        for obstacle in &self.obstacles {
+            if let Some(pushed) = obstacle.push_out(point) {
+                point = pushed;
+            }
+        }
+        point
+    }
+
+    // Moves from `from` towards `to`, stopping just short of the first obstacle in the way,
+    // returns where the mover ends up and whether it hit anything
+    pub fn sweep(&self, from: (f32, f32), to: (f32, f32)) -> ((f32, f32), bool) {
+        if self.line_of_sight(from, to) {
+            return (to, false);
+        }
+        // Already stuck inside, e.g. an obstacle was dropped on top of it
+        if self.is_blocked(from) {
+            return (self.resolve_collision(to), true);
+        }
+        let along = |t: f32| (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t);
+        let mut clear = 0.;
+        let mut hit = 1.;
+        for _ in 0..SWEEP_ITERATIONS {
+            let middle = (clear + hit) / 2.;
+            if self.line_of_sight(from, along(middle)) {
+                clear = middle;
+            } else {
+                hit = middle;
+            }
+        }
+        (along(clear), true)
+    }
+
+    // Direction an agent at `from` should head in to reach `to`, routing around obstacles
+    pub fn steer(&self, from: (f32, f32), to: (f32, f32)) -> (f32, f32) {
+        let direct = (to.0 - from.0, to.1 - from.1);
+        if vector_length(direct) == 0. {
+            return (0., 0.);
+        }
+        if self.obstacles.is_empty() || self.line_of_sight(from, to) {
+            return normalize_vector(direct);
+        }
+
+        match self.find_path(from, to) {
+            Some(path) => {
+                // Aim for the furthest waypoint that is directly reachable
+                let mut waypoint = path[0];
+                for point in path.iter() {
+                    if self.line_of_sight(from, *point) {
+                        waypoint = *point;
+                    } else {
+                        break;
+                    }
+                }
+                let heading = (waypoint.0 - from.0, waypoint.1 - from.1);
+                if vector_length(heading) == 0. {
+                    normalize_vector(direct)
+                } else {
+                    normalize_vector(heading)
+                }
+            }
+            None => normalize_vector(direct),
+        }
+    }
+
+    // Steers away from `threat`, picking an escape point that isn't behind a wall
+    pub fn flee(&self, from: (f32, f32), threat: (f32, f32), distance: f32) -> (f32, f32) {
+        let away = (from.0 - threat.0, from.1 - threat.1);
+        if vector_length(away) == 0. {
+            return (0., 0.);
+        }
+        let away = normalize_vector(away);
+        if self.obstacles.is_empty() {
+            return away;
+        }
+        // Try the straight escape first, then fan out on both sides
+        let base_angle = away.1.atan2(away.0);
+        for step in 0..8 {
+            let offset = (step as f32 / 2.).ceil() * std::f32::consts::FRAC_PI_4 / 2.;
+            let angle = if step % 2 == 0 {
+                base_angle + offset
+            } else {
+                base_angle - offset
+            };
+            let target = (
+                from.0 + angle.cos() * distance,
+                from.1 + angle.sin() * distance,
+            );
+            if self.bounds.contains(target) && self.line_of_sight(from, target) {
+                return (angle.cos(), angle.sin());
+            }
+        }
+        away
+    }
+
+    pub fn line_of_sight(&self, from: (f32, f32), to: (f32, f32)) -> bool {
+        !self
+            .obstacles
+            .iter()
+            .any(|obstacle| obstacle.intersects_segment(from, to))
+    }
+
+    // Grid based A*, returns the list of cell centers leading to `to`
+    pub fn find_path(&self, from: (f32, f32), to: (f32, f32)) -> Option<Vec<(f32, f32)>> {
+        // The grid is inflated around obstacles, so agents hugging a wall often stand in a
+        // blocked cell
+        let start = self.nearest_free_cell(self.cell_at(from)?)?;
+        let goal_cell = self.cell_at(to)?;
+        let goal = self.nearest_free_cell(goal_cell)?;
+        // Stop next to a blocked goal instead of walking into it
+        let end = if goal == goal_cell {
+            to
+        } else {
+            self.cell_center(goal)
+        };
+
+        let mut open = BinaryHeap::new();
+        let mut came_from: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+        let mut cost_so_far: HashMap<(usize, usize), f32> = HashMap::new();
+        open.push(SearchNode {
+            cost: 0.,
+            cell: start,
+        });
+        cost_so_far.insert(start, 0.);
+
+        let mut expanded = 0;
+        while let Some(current) = open.pop() {
+            if current.cell == goal {
+                let mut path = vec![end];
+                let mut cell = goal;
+                while let Some(previous) = came_from.get(&cell) {
+                    if *previous == start {
+                        break;
+                    }
+                    path.push(self.cell_center(*previous));
+                    cell = *previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            expanded += 1;
+            if expanded > MAX_SEARCH_NODES {
+                return None;
+            }
+
+            for neighbour in self.neighbours(current.cell) {
+                let step = if neighbour.0 != current.cell.0 && neighbour.1 != current.cell.1 {
+                    std::f32::consts::SQRT_2
+                } else {
+                    1.
+                };
+                let new_cost = cost_so_far[&current.cell] + step;
+                if new_cost < *cost_so_far.get(&neighbour).unwrap_or(&f32::MAX) {
+                    cost_so_far.insert(neighbour, new_cost);
+                    came_from.insert(neighbour, current.cell);
+                    let heuristic = vector_length((
+                        neighbour.0 as f32 - goal.0 as f32,
+                        neighbour.1 as f32 - goal.1 as f32,
+                    ));
+                    open.push(SearchNode {
+                        cost: new_cost + heuristic,
+                        cell: neighbour,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    fn neighbours(&self, cell: (usize, usize)) -> Vec<(usize, usize)> {
+        let mut result = Vec::with_capacity(8);
+        for dy in -1i32..=1 {
+            for dx in -1i32..=1 {
+                if dx == 0 && dy == 0 {
+                    continue;
+                }
+                let x = cell.0 as i32 + dx;
+                let y = cell.1 as i32 + dy;
+                if x < 0 || y < 0 || x >= self.columns as i32 || y >= self.rows as i32 {
+                    continue;
+                }
+                let neighbour = (x as usize, y as usize);
+                if self.cell_blocked(neighbour) {
+                    continue;
+                }
+                // Don't cut corners diagonally through walls
+                if dx != 0
+                    && dy != 0
+                    && (self.cell_blocked((x as usize, cell.1))
+                        || self.cell_blocked((cell.0, y as usize)))
+                {
+                    continue;
+                }
+                result.push(neighbour);
+            }
+        }
+        result
+    }
+
+    // The cell itself if it's free, otherwise the closest free cell in the rings around it
+    fn nearest_free_cell(&self, cell: (usize, usize)) -> Option<(usize, usize)> {
+        if !self.cell_blocked(cell) {
+            return Some(cell);
+        }
+        let mut closest = None;
+        let mut closest_distance = i32::MAX;
+        for ring in 1..=MAX_SNAP_DISTANCE {
+            for dy in -ring..=ring {
+                for dx in -ring..=ring {
+                    if dx.abs() != ring && dy.abs() != ring {
+                        continue;
+                    }
+                    let x = cell.0 as i32 + dx;
+                    let y = cell.1 as i32 + dy;
+                    if x < 0 || y < 0 || x >= self.columns as i32 || y >= self.rows as i32 {
+                        continue;
+                    }
+                    let candidate = (x as usize, y as usize);
+                    let distance = dx * dx + dy * dy;
+                    if !self.cell_blocked(candidate) && distance < closest_distance {
+                        closest_distance = distance;
+                        closest = Some(candidate);
+                    }
+                }
+            }
+            if closest.is_some() {
+                return closest;
+            }
+        }
+        None
+    }
+
+    fn cell_at(&self, point: (f32, f32)) -> Option<(usize, usize)> {
+        let column = ((point.0 - self.bounds.x) / NAV_CELL_SIZE).floor();
+        let row = ((point.1 - self.bounds.y) / NAV_CELL_SIZE).floor();
+        if column < 0. || row < 0. {
+            return None;
+        }
+        let cell = (
+            (column as usize).min(self.columns - 1),
+            (row as usize).min(self.rows - 1),
+        );
+        Some(cell)
+    }
+
+    fn cell_center(&self, cell: (usize, usize)) -> (f32, f32) {
+        (
+            self.bounds.x + (cell.0 as f32 + 0.5) * NAV_CELL_SIZE,
+            self.bounds.y + (cell.1 as f32 + 0.5) * NAV_CELL_SIZE,
+        )
+    }
+
+    fn cell_blocked(&self, cell: (usize, usize)) -> bool {
+        self.blocked[cell.1 * self.columns + cell.0]
+    }
+}
+
+fn closest_point_on_segment(point: (f32, f32), start: (f32, f32), end: (f32, f32)) -> (f32, f32) {
+    let segment = (end.0 - start.0, end.1 - start.1);
+    let length_squared = segment.0.powi(2) + segment.1.powi(2);
+    if length_squared == 0. {
+        return start;
+    }
+    let t = (((point.0 - start.0) * segment.0 + (point.1 - start.1) * segment.1) / length_squared)
+        .clamp(0., 1.);
+    (start.0 + segment.0 * t, start.1 + segment.1 * t)
+}
+
+fn segment_distance(point: (f32, f32), start: (f32, f32), end: (f32, f32)) -> f32 {
+    let closest = closest_point_on_segment(point, start, end);
+    vector_length((point.0 - closest.0, point.1 - closest.1))
+}
+
+fn cross(origin: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    (a.0 - origin.0) * (b.1 - origin.1) - (a.1 - origin.1) * (b.0 - origin.0)
+}
+
+fn segments_intersect(a: (f32, f32), b: (f32, f32), c: (f32, f32), d: (f32, f32)) -> bool {
+    let d1 = cross(c, d, a);
+    let d2 = cross(c, d, b);
+    let d3 = cross(a, b, c);
+    let d4 = cross(a, b, d);
+    ((d1 > 0.) != (d2 > 0.)) && ((d3 > 0.) != (d4 > 0.))
+}
+
+// Shortest distance between two segments, 0 if they cross
+fn segments_distance(a: (f32, f32), b: (f32, f32), c: (f32, f32), d: (f32, f32)) -> f32 {
+    if segments_intersect(a, b, c, d) {
+        return 0.;
+    }
+    segment_distance(a, c, d)
+        .min(segment_distance(b, c, d))
+        .min(segment_distance(c, a, b))
+        .min(segment_distance(d, a, b))
+}
+
+fn point_in_polygon(point: (f32, f32), points: &[(f32, f32)]) -> bool {
+    // Even-odd ray casting
+    let mut inside = false;
+    let mut j = points.len().wrapping_sub(1);
+    for i in 0..points.len() {
+        let (a, b) = (points[i], points[j]);
+        if (a.1 > point.1) != (b.1 > point.1)
+            && point.0 < (b.0 - a.0) * (point.1 - a.1) / (b.1 - a.1) + a.0
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terrain() -> Terrain {
+        Terrain::new(Bounds::new((0., 0.), 320., 320.))
+    }
+
+    fn square(x: f32, y: f32, size: f32) -> Vec<(f32, f32)> {
+        vec![(x, y), (x + size, y), (x + size, y + size), (x, y + size)]
+    }
+
+    #[test]
+    fn point_in_polygon_square() {
+        let points = square(10., 10., 20.);
+        assert!(point_in_polygon((20., 20.), &points));
+        assert!(point_in_polygon((11., 29.), &points));
+        assert!(!point_in_polygon((5., 20.), &points));
+        assert!(!point_in_polygon((20., 31.), &points));
+    }
+
+    #[test]
+    fn point_in_polygon_concave() {
+        // An L shape, the notch at the top right is outside
+        let points = vec![
+            (0., 0.),
+            (20., 0.),
+            (20., 10.),
+            (10., 10.),
+            (10., 20.),
+            (0., 20.),
+        ];
+        assert!(point_in_polygon((5., 15.), &points));
+        assert!(point_in_polygon((15., 5.), &points));
+        assert!(!point_in_polygon((15., 15.), &points));
+    }
+
+    #[test]
+    fn resolve_collision_pushes_out_of_rock() {
+        let mut terrain = terrain();
+        terrain.add_obstacle(Obstacle::Rock {
+            position: (100., 100.),
+            radius: 10.,
+        });
+        let resolved = terrain.resolve_collision((104., 100.));
+        assert!(!terrain.is_blocked(resolved));
+        assert!((resolved.0 - 110.).abs() < 0.1);
+        assert!((resolved.1 - 100.).abs() < 0.1);
+        // Free points are left alone
+        assert_eq!(terrain.resolve_collision((50., 50.)), (50., 50.));
+    }
+
+    #[test]
+    fn resolve_collision_pushes_past_polygon_edge() {
+        let mut terrain = terrain();
+        terrain.add_obstacle(Obstacle::Polygon {
+            points: square(100., 100., 40.),
+        });
+        let resolved = terrain.resolve_collision((102., 120.));
+        assert!(!terrain.is_blocked(resolved));
+        assert!(resolved.0 < 100.);
+        // A point right on the edge ends up outside too
+        let resolved = terrain.resolve_collision((100., 120.));
+        assert!(!terrain.is_blocked(resolved));
+    }
+
+    #[test]
+    fn sweep_stops_at_thin_fence() {
+        let mut terrain = terrain();
+        terrain.add_obstacle(Obstacle::Fence {
+            start: (100., 0.),
+            end: (100., 320.),
+            thickness: 1.,
+        });
+        // Both ends are clear of the fence, but the move crosses it
+        let (moved, collided) = terrain.sweep((95., 50.), (105., 50.));
+        assert!(collided);
+        assert!(moved.0 < 100.);
+        let (moved, collided) = terrain.sweep((80., 50.), (90., 50.));
+        assert!(!collided);
+        assert_eq!(moved, (90., 50.));
+    }
+
+    #[test]
+    fn find_path_goes_around_wall() {
+        let mut terrain = terrain();
+        terrain.add_obstacle(Obstacle::Fence {
+            start: (160., 0.),
+            end: (160., 240.),
+            thickness: 4.,
+        });
+        let path = terrain.find_path((80., 80.), (240., 80.)).unwrap();
+        assert_eq!(*path.last().unwrap(), (240., 80.));
+        assert!(path.iter().all(|point| !terrain.is_blocked(*point)));
+        // Has to pass below the end of the fence
+        assert!(path.iter().any(|point| point.1 > 240.));
+    }
+
+    #[test]
+    fn find_path_from_blocked_cell() {
+        let mut terrain = terrain();
+        terrain.add_obstacle(Obstacle::Rock {
+            position: (100., 100.),
+            radius: 20.,
+        });
+        // Just outside the rock, but inside the inflated navigation grid
+        let path = terrain.find_path((100., 123.), (100., 250.));
+        assert!(path.is_some());
+        // A goal inside the rock ends next to it
+        let path = terrain.find_path((100., 250.), (100., 100.)).unwrap();
+        assert!(!terrain.is_blocked(*path.last().unwrap()));
+    }
+
+    #[test]
+    fn find_path_fails_when_walled_in() {
+        let mut terrain = terrain();
+        terrain.add_obstacle(Obstacle::Fence {
+            start: (160., 0.),
+            end: (160., 320.),
+            thickness: 4.,
+        });
+        assert!(terrain.find_path((80., 80.), (240., 80.)).is_none());
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use rand::rngs::ThreadRng;
+use uuid::Uuid;
+
+use crate::{
+    agent::{Agent, DeathCause, State},
+    behaviour::{distance_squared, head_towards, normalize, Behaviour, Context},
+};
+
+const HOME_SCORE: f32 = 0.5;
+const DEFEND_SCORE: f32 = 0.6;
+// Close enough to the den to count as being home
+const HOME_DISTANCE: f32 = 20.;
+// A den left alone this long is given up for wherever the animal is
+const HOME_TIMEOUT: f32 = 120.;
+// Animals this hungry outside their territory take it as a sign it has run out of food
+const STARVING_HUNGER: f32 = 20.;
+// Intruders within this distance get bitten and chased off
+const SKIRMISH_DISTANCE: f32 = 6.;
+const SKIRMISH_DAMAGE: f32 = 0.5;
+// How hard a bitten intruder is driven away
+const SKIRMISH_PUSH: f32 = 2.;
+
+fn distance_to(agent: &Agent, point: (f32, f32)) -> f32 {
+    ((agent.position.0 - point.0).powi(2) + (agent.position.1 - point.1).powi(2)).sqrt()
+}
+
+fn settle(agent: &mut Agent, context: &Context) {
+    agent.home = Some((agent.position.0, agent.position.1));
+    agent.home_seen = context.time;
+}
+
+// Territorial animals settle down where they first find themselves, and move the den when
+// they've been kept from it too long or are starving away from it
+pub fn update_home(agent: &mut Agent, context: &Context) {
+    let home = match agent.home {
+        Some(home) => home,
+        None => {
+            settle(agent, context);
+            return;
+        }
+    };
+    if distance_to(agent, home) <= context.genotype.territory_size.max(HOME_DISTANCE) {
+        agent.home_seen = context.time;
+    } else if context.time - agent.home_seen > HOME_TIMEOUT || agent.hunger < STARVING_HUNGER {
+        settle(agent, context);
+    }
+}
+
+// An animal of the same kind inside the territory that neither shares the pack nor dens inside
+// it, those are family and tolerated
+fn is_intruder(agent: &Agent, other: &Agent, context: &Context, home: (f32, f32)) -> bool {
+    let territory = context.genotype.territory_size;
+    if other.dead
+        || other.id == agent.id
+        || other.kind.species() != agent.kind.species()
+        || (agent.pack.is_some() && other.pack == agent.pack)
+        || distance_to(other, home) > territory
+    {
+        return false;
+    }
+    match other.home {
+        Some(other_home) => {
+            let offset = (other_home.0 - home.0, other_home.1 - home.1);
+            offset.0.powi(2) + offset.1.powi(2) >= territory.powi(2)
+        }
+        None => true,
+    }
+}
+
+// The intruder already being chased while it's still inside, otherwise the closest one
+fn closest_intruder<'a>(agent: &Agent, context: &Context<'a>) -> Option<&'a Agent> {
+    let home = agent.home?;
+    if let State::Defending(target) = agent.state {
+        if let Some(intruder) = context.get_nearby(target) {
+            if is_intruder(agent, intruder, context, home) {
+                return Some(intruder);
+            }
+        }
+    }
+    let mut closest_distance = f32::MAX;
+    let mut closest = None;
+    for other in context.neighbours(agent) {
+        if !is_intruder(agent, other, context, home) {
+            continue;
+        }
+        let distance = distance_squared(agent, other);
+        if distance < closest_distance {
+            closest_distance = distance;
+            closest = Some(other);
+        }
+    }
+    closest
+}
+
+// Goes back to the den at night, or whenever it strays out of its territory
+pub struct ReturnHome;
+impl Behaviour for ReturnHome {
+    fn name(&self) -> &'static str {
+        "ReturnHome"
+    }
+
+    fn is_running(&self, agent: &Agent, _context: &Context) -> bool {
+        matches!(agent.state, State::Homing)
+    }
+
+    fn score(&self, agent: &Agent, context: &Context) -> f32 {
+        let home = match agent.home {
+            Some(home) => home,
+            None => return 0.,
+        };
+        let distance = distance_to(agent, home);
+        if distance < HOME_DISTANCE {
+            return 0.;
+        }
+        let territory = context.genotype.territory_size.max(HOME_DISTANCE);
+        let night = if context.clock.is_night() { 1. } else { 0. };
+        let straying = ((distance - territory) / territory).clamp(0., 1.);
+        HOME_SCORE * f32::max(night, straying)
+    }
+
+    fn act(
+        &self,
+        agent: &mut Agent,
+        context: &Context,
+        _thread_rng: &mut ThreadRng,
+        _modified_agents: &mut HashMap<Uuid, Agent>,
+    ) {
+        match agent.home {
+            Some(home) => {
+                agent.state = State::Homing;
+                head_towards(agent, context, home, context.genotype.movement_speed);
+            }
+            None => agent.state = State::Idle,
+        }
+    }
+}
+
+// Chases animals of the same kind out of the territory
+pub struct Defend;
+impl Behaviour for Defend {
+    fn name(&self) -> &'static str {
+        "Defend"
+    }
+
+    fn is_running(&self, agent: &Agent, _context: &Context) -> bool {
+        matches!(agent.state, State::Defending(_))
+    }
+
+    fn score(&self, agent: &Agent, context: &Context) -> f32 {
+        let (intruder, home) = match (closest_intruder(agent, context), agent.home) {
+            (Some(intruder), Some(home)) => (intruder, home),
+            _ => return 0.,
+        };
+        // Exhausted animals let it slide
+        if agent.exhausted {
+            return 0.;
+        }
+        // Fear holds animals back at the edge of the territory, but not from an intruder right
+        // at the den
+        let territory = context.genotype.territory_size.max(HOME_DISTANCE);
+        let closeness = 1. - (distance_to(intruder, home) / territory).min(1.);
+        DEFEND_SCORE * (1. - context.genotype.fear.max(0.) * (1. - closeness)).clamp(0., 1.)
+    }
+
+    fn act(
+        &self,
+        agent: &mut Agent,
+        context: &Context,
+        _thread_rng: &mut ThreadRng,
+        modified_agents: &mut HashMap<Uuid, Agent>,
+    ) {
+        let intruder = match closest_intruder(agent, context) {
+            Some(intruder) => intruder,
+            None => {
+                agent.state = State::Idle;
+                return;
+            }
+        };
+        agent.state = State::Defending(intruder.id);
+        let top_speed = context.genotype.movement_speed * context.genotype.sprint_multiplier;
+        let target = (intruder.position.0, intruder.position.1);
+        head_towards(agent, context, target, top_speed);
+
+        if distance_squared(agent, intruder) < SKIRMISH_DISTANCE.powi(2) {
+            let away = normalize((
+                intruder.position.0 - agent.position.0,
+                intruder.position.1 - agent.position.1,
+            ));
+            let mut intruder = intruder.clone();
+            intruder.health -= SKIRMISH_DAMAGE;
+            intruder.acceleration.0 += away.0 * SKIRMISH_PUSH;
+            intruder.acceleration.1 += away.1 * SKIRMISH_PUSH;
+            if intruder.health <= 0. {
+                intruder.die(DeathCause::Fighting);
+            }
+            modified_agents.insert(intruder.id, intruder);
+        }
+    }
+}